@@ -0,0 +1,174 @@
+//! The memory interface the CPU drives, and a flat 64 KiB RAM.
+
+/// Everything the CPU can address. Each call to `read` or `write` is one CPU
+/// cycle, so a bus can clock other hardware from them.
+pub trait Bus {
+    /// Reads a byte, with any side effects a real access would have.
+    fn read(&mut self, address: u16) -> u8;
+    /// Writes a byte.
+    fn write(&mut self, address: u16, value: u8);
+
+    /// Reads without side effects, for tracers and debuggers. Buses with
+    /// memory-mapped registers should override this so that looking does not
+    /// acknowledge or clear anything.
+    fn peek(&mut self, address: u16) -> u8 {
+        self.read(address)
+    }
+
+    /// Reports an address the bus could not service since the last call.
+    fn take_fault(&mut self) -> Option<u16> {
+        None
+    }
+}
+
+/// Contents of RAM before software has written to it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RamPattern {
+    /// Every byte $00.
+    Zeros,
+    /// Every byte $FF.
+    Ones,
+    /// Pseudo-random bytes, the same for a given seed.
+    Random {
+        /// Seed for the generator; any value, including zero, is fine.
+        seed: u64,
+    },
+    /// Four bytes of $00 followed by four of $FF, as seen on most NES consoles.
+    Nes,
+    /// 64 bytes of $00 followed by 64 of $FF, as on the Commodore 64.
+    Commodore64,
+}
+
+impl RamPattern {
+    fn fill(self, memory: &mut [u8]) {
+        match self {
+            RamPattern::Zeros => memory.iter_mut().for_each(|byte| *byte = 0x00),
+            RamPattern::Ones => memory.iter_mut().for_each(|byte| *byte = 0xFF),
+            RamPattern::Random { seed } => {
+                let mut state = seed;
+                for byte in memory.iter_mut() {
+                    *byte = split_mix(&mut state) as u8;
+                }
+            }
+            RamPattern::Nes => Self::fill_blocks(memory, 4),
+            RamPattern::Commodore64 => Self::fill_blocks(memory, 64),
+        }
+    }
+
+    fn fill_blocks(memory: &mut [u8], size: usize) {
+        for (address, byte) in memory.iter_mut().enumerate() {
+            *byte = if (address / size) & 1 == 0 {
+                0x00
+            } else {
+                0xFF
+            };
+        }
+    }
+}
+
+// SplitMix64, which gives well-mixed output for any seed including zero.
+fn split_mix(state: &mut u64) -> u64 {
+    *state = state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    let mut value = *state;
+    value = (value ^ (value >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    value = (value ^ (value >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    value ^ (value >> 31)
+}
+
+/// 64 KiB of RAM covering the whole address space.
+pub struct Ram {
+    memory: [u8; 0x10000],
+}
+
+impl Ram {
+    /// Zero-filled RAM.
+    pub fn new() -> Self {
+        Ram::with_pattern(RamPattern::Zeros)
+    }
+
+    /// RAM filled with `pattern`.
+    pub fn with_pattern(pattern: RamPattern) -> Self {
+        let mut ram = Ram {
+            memory: [0; 0x10000],
+        };
+        ram.fill(pattern);
+        ram
+    }
+
+    /// Overwrites all of RAM with `pattern`.
+    pub fn fill(&mut self, pattern: RamPattern) {
+        pattern.fill(&mut self.memory);
+    }
+
+    /// Copies `data` in from `address`, wrapping at $FFFF.
+    pub fn load(&mut self, address: u16, data: &[u8]) {
+        for (offset, &value) in data.iter().enumerate() {
+            let target = address.wrapping_add(offset as u16);
+            self.memory[target as usize] = value;
+        }
+    }
+}
+
+impl Default for Ram {
+    fn default() -> Self {
+        Ram::new()
+    }
+}
+
+impl Bus for Ram {
+    fn read(&mut self, address: u16) -> u8 {
+        self.memory[address as usize]
+    }
+
+    fn write(&mut self, address: u16, value: u8) {
+        self.memory[address as usize] = value;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn filled(pattern: RamPattern) -> Ram {
+        Ram::with_pattern(pattern)
+    }
+
+    #[test]
+    fn constant_patterns() {
+        assert!(filled(RamPattern::Zeros)
+            .memory
+            .iter()
+            .all(|&byte| byte == 0x00));
+        assert!(filled(RamPattern::Ones)
+            .memory
+            .iter()
+            .all(|&byte| byte == 0xFF));
+    }
+
+    #[test]
+    fn block_patterns_alternate() {
+        let nes = filled(RamPattern::Nes);
+        assert_eq!(nes.memory[..10], [0, 0, 0, 0, 0xFF, 0xFF, 0xFF, 0xFF, 0, 0]);
+
+        let c64 = filled(RamPattern::Commodore64);
+        assert_eq!((c64.memory[63], c64.memory[64]), (0x00, 0xFF));
+        assert_eq!((c64.memory[127], c64.memory[128]), (0xFF, 0x00));
+    }
+
+    #[test]
+    fn random_pattern_depends_only_on_the_seed() {
+        let first = filled(RamPattern::Random { seed: 0 });
+        let again = filled(RamPattern::Random { seed: 0 });
+        let other = filled(RamPattern::Random { seed: 1 });
+        assert!(first.memory[..] == again.memory[..]);
+        assert!(first.memory[..] != other.memory[..]);
+        assert!(first.memory.iter().any(|&byte| byte != first.memory[0]));
+    }
+
+    #[test]
+    fn fill_overwrites_existing_contents() {
+        let mut ram = filled(RamPattern::Ones);
+        ram.fill(RamPattern::Zeros);
+        assert_eq!(ram.read(0x1234), 0x00);
+    }
+}
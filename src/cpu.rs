@@ -1,1401 +1,2773 @@
-pub struct Cpu {
+//! The CPU core.
+
+use crate::bus::{Bus, Ram};
+use crate::error::CpuError;
+use crate::opcode::{AddressingMode, Instruction, Opcode, CMOS_OPCODES, NMOS_OPCODES};
+use crate::status::StatusFlags;
+#[cfg(feature = "std")]
+use crate::trace::Tracer;
+use crate::watch::{AccessKind, MemoryAccess};
+#[cfg(feature = "alloc")]
+use crate::watch::{WatchId, Watches};
+#[cfg(feature = "alloc")]
+use alloc::boxed::Box;
+use core::fmt;
+#[cfg(feature = "alloc")]
+use core::ops::RangeInclusive;
+
+const NMI_VECTOR: u16 = 0xFFFA;
+const RESET_VECTOR: u16 = 0xFFFC;
+const IRQ_VECTOR: u16 = 0xFFFE;
+
+const WAI_OPCODE: u8 = 0xCB;
+
+// Longest access sequence of any instruction or interrupt, with headroom
+// for the speculative tail of a replayed instruction.
+const MAX_ACCESSES: usize = 16;
+
+// Bits of A that survive the analog bus conflict in XAA and LXA.
+const UNSTABLE_MAGIC: u8 = 0xEE;
+
+/// Which member of the 6502 family to emulate.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CpuVariant {
+    /// The original NMOS 6502, with its undocumented opcodes and quirks.
+    Nmos6502,
+    /// The NES CPU: an NMOS 6502 whose decimal mode does nothing.
+    Ricoh2A03,
+    /// The WDC 65C02, with its added instructions and bug fixes.
+    Wdc65C02,
+}
+
+impl CpuVariant {
+    /// The opcode table this variant decodes with.
+    pub fn opcodes(self) -> &'static [Opcode; 256] {
+        match self {
+            CpuVariant::Nmos6502 | CpuVariant::Ricoh2A03 => &NMOS_OPCODES,
+            CpuVariant::Wdc65C02 => &CMOS_OPCODES,
+        }
+    }
+}
+
+/// A hardware interrupt sequence.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Interrupt {
+    /// Non-maskable interrupt, through the vector at $FFFA.
+    Nmi,
+    /// Maskable interrupt, through the vector at $FFFE.
+    Irq,
+}
+
+/// Summary of one completed step.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct StepInfo {
+    /// Address of the instruction, or the return address of an interrupt.
+    pub pc: u16,
+    /// The opcode run, or $00 for an interrupt sequence.
+    pub opcode: u8,
+    /// Cycles the step took.
+    pub cycles: u8,
+    /// Set when the step ran an interrupt sequence instead of an instruction.
+    pub interrupt: Option<Interrupt>,
+}
+
+/// When `run`, `run_for_cycles` and `run_until` return, besides their own
+/// limits. Checked after every step; the default never stops.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct StopConditions {
+    /// Stop once PC reaches this address.
+    pub breakpoint: Option<u16>,
+    /// Stop after a BRK instruction.
+    pub on_break: bool,
+    /// Stop after an instruction that leaves PC where it started, such as a
+    /// `JMP *` used to signal the end of a test.
+    pub on_self_jump: bool,
+    /// Stop after this many steps of one run call.
+    pub instruction_limit: Option<u64>,
+}
+
+/// Why a run call returned.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StopReason {
+    /// `run_for_cycles` used up its cycles.
+    CyclesElapsed,
+    /// The `run_until` predicate returned true.
+    Predicate,
+    /// PC reached `StopConditions::breakpoint`.
+    Breakpoint {
+        /// The breakpoint address.
+        pc: u16,
+    },
+    /// A BRK ran with `StopConditions::on_break` set.
+    Break {
+        /// Address of the BRK.
+        pc: u16,
+    },
+    /// A JAM or STP opcode halted the CPU.
+    Halted {
+        /// Address of the opcode.
+        pc: u16,
+        /// The opcode byte.
+        opcode: u8,
+    },
+    /// An instruction jumped to itself with `StopConditions::on_self_jump`
+    /// set.
+    SelfJump {
+        /// Address of the instruction.
+        pc: u16,
+    },
+    /// `StopConditions::instruction_limit` steps were run.
+    InstructionLimit,
+    /// A watchpoint matched this access during the last step.
+    Watchpoint(MemoryAccess),
+    /// A step failed.
+    Error(CpuError),
+}
+
+/// The programmer-visible registers.
+#[derive(Clone, Copy, Default, PartialEq, Eq)]
+pub struct CpuState {
+    /// A.
+    pub accumulator: u8,
+    /// X.
+    pub index_x: u8,
+    /// Y.
+    pub index_y: u8,
+    /// P.
+    pub status: StatusFlags,
+    /// SP, an offset into page one.
+    pub stack_pointer: u8,
+    /// PC.
+    pub program_counter: u16,
+}
+
+impl fmt::Debug for CpuState {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "A:{:02X} X:{:02X} Y:{:02X} P:{:02X} SP:{:02X} PC:{:04X}",
+            self.accumulator,
+            self.index_x,
+            self.index_y,
+            self.status.bits(),
+            self.stack_pointer,
+            self.program_counter
+        )
+    }
+}
+
+// Interrupt inputs as seen by the CPU at the start of a cycle.
+#[derive(Clone, Copy, Default)]
+struct Signals {
+    nmi: bool,
+    irq: bool,
+}
+
+enum Slot {
+    Live,
+    Logged(usize),
+    Recorded(usize),
+    Speculative,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Access {
+    Read,
+    Write,
+    Modify,
+}
+
+#[derive(Clone, Copy)]
+struct Snapshot {
+    state: CpuState,
+    cycles: u64,
+    halted: bool,
+    waiting: bool,
+    service_pending: bool,
+}
+
+// In cycle-stepped mode the current instruction is re-run from its starting
+// registers on every tick. Accesses already performed are answered from the
+// log, exactly one new access reaches the bus, and anything after it is
+// speculative and discarded.
+struct Replay {
+    snapshot: Option<Snapshot>,
+    values: [u8; MAX_ACCESSES],
+    signals: [Signals; MAX_ACCESSES],
+    performed: usize,
+    cursor: usize,
+    active: bool,
+    advanced: bool,
+    incomplete: bool,
+}
+
+impl Replay {
+    fn new() -> Self {
+        Replay {
+            snapshot: None,
+            values: [0; MAX_ACCESSES],
+            signals: [Signals::default(); MAX_ACCESSES],
+            performed: 0,
+            cursor: 0,
+            active: false,
+            advanced: false,
+            incomplete: false,
+        }
+    }
+}
+
+/// A 6502 family CPU driving a bus. Instructions run either whole, with
+/// `step` and the run calls, or one bus cycle at a time with `tick`.
+pub struct Cpu<B: Bus = Ram> {
     accumulator: u8,
     index_x: u8,
     index_y: u8,
     program_counter: u16,
     stack_pointer: u8,
-    processor_status: u8,
-    memory: [u8; 0xFF],
+    processor_status: StatusFlags,
+    cycles: u64,
+    variant: CpuVariant,
+    halted: bool,
+    waiting: bool,
+    // Cycles still to be spent off the bus before the next instruction.
+    stall: u32,
+    irq_line: bool,
+    nmi_edge: bool,
+    // Whether an interrupt was seen before the last cycle of the previous
+    // instruction, and will be serviced before the next one.
+    service_pending: bool,
+    poll: bool,
+    nmi_sampled: bool,
+    nmi_taken: bool,
+    undocumented_opcodes: bool,
+    stop_conditions: StopConditions,
+    #[cfg(feature = "alloc")]
+    watches: Watches,
+    #[cfg(feature = "std")]
+    tracer: Option<Tracer>,
+    replay: Replay,
+    bus: B,
 }
 
-pub enum AddressingMode {
-    Implicit,
-    Accumulator,
-    Immediate,
-    ZeroPage,
-    ZeroPageX,
-    ZeroPageY,
-    Relative,
-    Absolute,
-    AbsoluteX,
-    AbsoluteY,
-    Indirect,
-    IndexedIndirect,
-    IndirectIndexed,
+impl Cpu {
+    /// An NMOS 6502 on 64 KiB of zeroed RAM.
+    pub fn new() -> Self {
+        Cpu::with_bus(Ram::new())
+    }
 }
 
-pub enum Instruction {
-    Adc,
-    And,
-    Asl,
-    Bcc,
-    Bcs,
-    Beq,
-    Bit,
-    Bmi,
-    Bne,
-    Bpl,
-    Brk,
-    Bvc,
-    Bvs,
-    Clc,
-    Cld,
-    Cli,
-    Clv,
-    Cmp,
-    Cpx,
-    Cpy,
-    Dec,
-    Dex,
-    Dey,
-    Eor,
-    Inc,
-    Inx,
-    Iny,
-    Jmp,
-    Jsr,
-    Lda,
-    Ldx,
-    Ldy,
-    Lsr,
-    Nop,
-    Ora,
-    Pha,
-    Php,
-    Pla,
-    Plp,
-    Rol,
-    Ror,
-    Rti,
-    Rts,
-    Sbc,
-    Sec,
-    Sed,
-    Sei,
-    Sta,
-    Stx,
-    Sty,
-    Tax,
-    Tay,
-    Tsx,
-    Txa,
-    Txs,
-    Tya,
+impl Default for Cpu {
+    fn default() -> Self {
+        Cpu::new()
+    }
 }
 
-pub struct Opcode {
-    instruction: Instruction,
-    mode: AddressingMode,
-    bytes: u8,
-    cycles: u8,
-}
+impl<B: Bus> Cpu<B> {
+    /// An NMOS 6502 on `bus`.
+    pub fn with_bus(bus: B) -> Self {
+        Cpu::with_variant(CpuVariant::Nmos6502, bus)
+    }
 
-impl Cpu {
-    pub fn new() -> Self {
+    /// A CPU of the given variant on `bus`. The registers are all zero until
+    /// `power_on` or `set_state`, and undocumented opcodes are enabled.
+    pub fn with_variant(variant: CpuVariant, bus: B) -> Self {
         Cpu {
             accumulator: 0,
             index_x: 0,
             index_y: 0,
-            program_counter: 0x34,
-            stack_pointer: 0xFD,
-            processor_status: 0,
-            memory: [0; 0xFF],
+            program_counter: 0,
+            stack_pointer: 0,
+            processor_status: StatusFlags::default(),
+            cycles: 0,
+            variant,
+            halted: false,
+            waiting: false,
+            stall: 0,
+            irq_line: false,
+            nmi_edge: false,
+            service_pending: false,
+            poll: false,
+            nmi_sampled: false,
+            nmi_taken: false,
+            undocumented_opcodes: true,
+            stop_conditions: StopConditions::default(),
+            #[cfg(feature = "alloc")]
+            watches: Watches::default(),
+            #[cfg(feature = "std")]
+            tracer: None,
+            replay: Replay::new(),
+            bus,
         }
     }
 
-    pub fn run(&mut self) {
-        let address = self.fetch();
-        let opcode = self.decode(address);
-        self.execute(opcode)
+    /// The emulated variant.
+    pub fn variant(&self) -> CpuVariant {
+        self.variant
     }
 
-    fn fetch(&mut self) -> u8 {
-        self.memory[self.program_counter as usize]
+    /// Enables or disables the undocumented opcodes. While disabled, fetching
+    /// one fails with `CpuError::IllegalOpcode`.
+    pub fn set_undocumented_opcodes(&mut self, enabled: bool) {
+        self.undocumented_opcodes = enabled;
     }
 
-    fn decode(&mut self, address: u8) -> Opcode {
-        match address {
-            0x00 => Opcode {
-                instruction: Instruction::Brk,
-                mode: AddressingMode::Implicit,
-                bytes: 1,
-                cycles: 7,
-            },
-            0x01 => Opcode {
-                instruction: Instruction::Ora,
-                mode: AddressingMode::IndexedIndirect,
-                bytes: 2,
-                cycles: 6,
-            },
-            0x05 => Opcode {
-                instruction: Instruction::Ora,
-                mode: AddressingMode::ZeroPage,
-                bytes: 2,
-                cycles: 3,
-            },
-            0x06 => Opcode {
-                instruction: Instruction::Asl,
-                mode: AddressingMode::ZeroPage,
-                bytes: 2,
-                cycles: 5,
-            },
-            0x08 => Opcode {
-                instruction: Instruction::Php,
-                mode: AddressingMode::Implicit,
-                bytes: 1,
-                cycles: 3,
-            },
-            0x09 => Opcode {
-                instruction: Instruction::Ora,
-                mode: AddressingMode::Immediate,
-                bytes: 2,
-                cycles: 2,
-            },
-            0x0A => Opcode {
-                instruction: Instruction::Asl,
-                mode: AddressingMode::Accumulator,
-                bytes: 1,
-                cycles: 2,
-            },
-            0x0D => Opcode {
-                instruction: Instruction::Ora,
-                mode: AddressingMode::Absolute,
-                bytes: 3,
-                cycles: 4,
-            },
-            0x0E => Opcode {
-                instruction: Instruction::Asl,
-                mode: AddressingMode::Absolute,
-                bytes: 3,
-                cycles: 6,
-            },
-            0x10 => Opcode {
-                instruction: Instruction::Bpl,
-                mode: AddressingMode::Relative,
-                bytes: 2,
-                cycles: 2,
-            },
-            0x11 => Opcode {
-                instruction: Instruction::Ora,
-                mode: AddressingMode::IndirectIndexed,
-                bytes: 2,
-                cycles: 5,
-            },
-            0x15 => Opcode {
-                instruction: Instruction::Ora,
-                mode: AddressingMode::ZeroPageX,
-                bytes: 2,
-                cycles: 4,
-            },
-            0x16 => Opcode {
-                instruction: Instruction::Asl,
-                mode: AddressingMode::ZeroPageX,
-                bytes: 2,
-                cycles: 6,
-            },
-            0x18 => Opcode {
-                instruction: Instruction::Clc,
-                mode: AddressingMode::Implicit,
-                bytes: 1,
-                cycles: 2,
-            },
-            0x19 => Opcode {
-                instruction: Instruction::Ora,
-                mode: AddressingMode::AbsoluteY,
-                bytes: 3,
-                cycles: 4,
-            },
-            0x1D => Opcode {
-                instruction: Instruction::Ora,
-                mode: AddressingMode::AbsoluteX,
-                bytes: 3,
-                cycles: 4,
-            },
-            0x1E => Opcode {
-                instruction: Instruction::Asl,
-                mode: AddressingMode::AbsoluteX,
-                bytes: 3,
-                cycles: 7,
-            },
-            0x20 => Opcode {
-                instruction: Instruction::Jsr,
-                mode: AddressingMode::Absolute,
-                bytes: 3,
-                cycles: 6,
-            },
-            0x21 => Opcode {
-                instruction: Instruction::And,
-                mode: AddressingMode::IndexedIndirect,
-                bytes: 2,
-                cycles: 6,
-            },
-            0x24 => Opcode {
-                instruction: Instruction::Bit,
-                mode: AddressingMode::ZeroPage,
-                bytes: 2,
-                cycles: 3,
-            },
-            0x25 => Opcode {
-                instruction: Instruction::And,
-                mode: AddressingMode::ZeroPage,
-                bytes: 2,
-                cycles: 3,
-            },
-            0x26 => Opcode {
-                instruction: Instruction::Rol,
-                mode: AddressingMode::ZeroPage,
-                bytes: 2,
-                cycles: 5,
-            },
-            0x28 => Opcode {
-                instruction: Instruction::Plp,
-                mode: AddressingMode::Implicit,
-                bytes: 1,
-                cycles: 4,
-            },
-            0x29 => Opcode {
-                instruction: Instruction::And,
-                mode: AddressingMode::Immediate,
-                bytes: 2,
-                cycles: 2,
-            },
-            0x2A => Opcode {
-                instruction: Instruction::Rol,
-                mode: AddressingMode::Accumulator,
-                bytes: 1,
-                cycles: 2,
-            },
-            0x2C => Opcode {
-                instruction: Instruction::Bit,
-                mode: AddressingMode::Absolute,
-                bytes: 3,
-                cycles: 4,
-            },
-            0x2D => Opcode {
-                instruction: Instruction::And,
-                mode: AddressingMode::Absolute,
-                bytes: 3,
-                cycles: 4,
-            },
-            0x2E => Opcode {
-                instruction: Instruction::Rol,
-                mode: AddressingMode::Absolute,
-                bytes: 3,
-                cycles: 6,
-            },
-            0x30 => Opcode {
-                instruction: Instruction::Bmi,
-                mode: AddressingMode::Relative,
-                bytes: 2,
-                cycles: 2,
-            },
-            0x31 => Opcode {
-                instruction: Instruction::And,
-                mode: AddressingMode::IndirectIndexed,
-                bytes: 2,
-                cycles: 5,
-            },
-            0x35 => Opcode {
-                instruction: Instruction::And,
-                mode: AddressingMode::ZeroPageX,
-                bytes: 2,
-                cycles: 4,
-            },
-            0x36 => Opcode {
-                instruction: Instruction::Rol,
-                mode: AddressingMode::ZeroPageX,
-                bytes: 2,
-                cycles: 6,
-            },
-            0x38 => Opcode {
-                instruction: Instruction::Sec,
-                mode: AddressingMode::Implicit,
-                bytes: 1,
-                cycles: 2,
-            },
-            0x39 => Opcode {
-                instruction: Instruction::And,
-                mode: AddressingMode::AbsoluteY,
-                bytes: 3,
-                cycles: 4,
-            },
-            0x3D => Opcode {
-                instruction: Instruction::And,
-                mode: AddressingMode::AbsoluteX,
-                bytes: 3,
-                cycles: 4,
-            },
-            0x3E => Opcode {
-                instruction: Instruction::Rol,
-                mode: AddressingMode::AbsoluteX,
-                bytes: 3,
-                cycles: 7,
-            },
-            0x40 => Opcode {
-                instruction: Instruction::Rti,
-                mode: AddressingMode::Implicit,
-                bytes: 1,
-                cycles: 6,
-            },
-            0x41 => Opcode {
-                instruction: Instruction::Eor,
-                mode: AddressingMode::IndexedIndirect,
-                bytes: 2,
-                cycles: 6,
-            },
-            0x45 => Opcode {
-                instruction: Instruction::Eor,
-                mode: AddressingMode::ZeroPage,
-                bytes: 2,
-                cycles: 3,
-            },
-            0x46 => Opcode {
-                instruction: Instruction::Lsr,
-                mode: AddressingMode::ZeroPage,
-                bytes: 2,
-                cycles: 5,
-            },
-            0x48 => Opcode {
-                instruction: Instruction::Pha,
-                mode: AddressingMode::Implicit,
-                bytes: 1,
-                cycles: 3,
-            },
-            0x49 => Opcode {
-                instruction: Instruction::Eor,
-                mode: AddressingMode::Immediate,
-                bytes: 2,
-                cycles: 2,
-            },
-            0x4A => Opcode {
-                instruction: Instruction::Lsr,
-                mode: AddressingMode::Accumulator,
-                bytes: 1,
-                cycles: 2,
-            },
-            0x4C => Opcode {
-                instruction: Instruction::Jmp,
-                mode: AddressingMode::Absolute,
-                bytes: 3,
-                cycles: 3,
-            },
-            0x4D => Opcode {
-                instruction: Instruction::Eor,
-                mode: AddressingMode::Absolute,
-                bytes: 3,
-                cycles: 4,
-            },
-            0x4E => Opcode {
-                instruction: Instruction::Lsr,
-                mode: AddressingMode::Absolute,
-                bytes: 3,
-                cycles: 6,
-            },
-            0x50 => Opcode {
-                instruction: Instruction::Bvc,
-                mode: AddressingMode::Relative,
-                bytes: 2,
-                cycles: 2,
-            },
-            0x51 => Opcode {
-                instruction: Instruction::Eor,
-                mode: AddressingMode::IndirectIndexed,
-                bytes: 2,
-                cycles: 5,
-            },
-            0x55 => Opcode {
-                instruction: Instruction::Eor,
-                mode: AddressingMode::ZeroPageX,
-                bytes: 2,
-                cycles: 4,
-            },
-            0x56 => Opcode {
-                instruction: Instruction::Lsr,
-                mode: AddressingMode::ZeroPageX,
-                bytes: 2,
-                cycles: 6,
-            },
-            0x58 => Opcode {
-                instruction: Instruction::Cli,
-                mode: AddressingMode::Implicit,
-                bytes: 1,
-                cycles: 2,
-            },
-            0x59 => Opcode {
-                instruction: Instruction::Eor,
-                mode: AddressingMode::AbsoluteY,
-                bytes: 3,
-                cycles: 4,
-            },
-            0x5D => Opcode {
-                instruction: Instruction::Eor,
-                mode: AddressingMode::AbsoluteX,
-                bytes: 3,
-                cycles: 4,
-            },
-            0x5E => Opcode {
-                instruction: Instruction::Lsr,
-                mode: AddressingMode::AbsoluteX,
-                bytes: 3,
-                cycles: 7,
-            },
-            0x60 => Opcode {
-                instruction: Instruction::Rts,
-                mode: AddressingMode::Implicit,
-                bytes: 1,
-                cycles: 6,
-            },
-            0x61 => Opcode {
-                instruction: Instruction::Adc,
-                mode: AddressingMode::IndexedIndirect,
-                bytes: 2,
-                cycles: 6,
-            },
-            0x65 => Opcode {
-                instruction: Instruction::Adc,
-                mode: AddressingMode::ZeroPage,
-                bytes: 2,
-                cycles: 3,
-            },
-            0x66 => Opcode {
-                instruction: Instruction::Ror,
-                mode: AddressingMode::ZeroPage,
-                bytes: 2,
-                cycles: 5,
-            },
-            0x68 => Opcode {
-                instruction: Instruction::Pla,
-                mode: AddressingMode::Implicit,
-                bytes: 1,
-                cycles: 4,
-            },
-            0x69 => Opcode {
-                instruction: Instruction::Adc,
-                mode: AddressingMode::Immediate,
-                bytes: 2,
-                cycles: 2,
-            },
-            0x6A => Opcode {
-                instruction: Instruction::Ror,
-                mode: AddressingMode::Accumulator,
-                bytes: 1,
-                cycles: 2,
-            },
-            0x6C => Opcode {
-                instruction: Instruction::Jmp,
-                mode: AddressingMode::Indirect,
-                bytes: 3,
-                cycles: 5,
-            },
-            0x6D => Opcode {
-                instruction: Instruction::Adc,
-                mode: AddressingMode::Absolute,
-                bytes: 3,
-                cycles: 4,
-            },
-            0x6E => Opcode {
-                instruction: Instruction::Ror,
-                mode: AddressingMode::Absolute,
-                bytes: 3,
-                cycles: 6,
-            },
-            0x70 => Opcode {
-                instruction: Instruction::Bvs,
-                mode: AddressingMode::Relative,
-                bytes: 2,
-                cycles: 2,
-            },
-            0x71 => Opcode {
-                instruction: Instruction::Adc,
-                mode: AddressingMode::IndirectIndexed,
-                bytes: 2,
-                cycles: 5,
-            },
-            0x75 => Opcode {
-                instruction: Instruction::Adc,
-                mode: AddressingMode::ZeroPageX,
-                bytes: 2,
-                cycles: 4,
-            },
-            0x76 => Opcode {
-                instruction: Instruction::Ror,
-                mode: AddressingMode::ZeroPageX,
-                bytes: 2,
-                cycles: 6,
-            },
-            0x78 => Opcode {
-                instruction: Instruction::Sei,
-                mode: AddressingMode::Implicit,
-                bytes: 1,
-                cycles: 2,
-            },
-            0x79 => Opcode {
-                instruction: Instruction::Adc,
-                mode: AddressingMode::AbsoluteY,
-                bytes: 3,
-                cycles: 4,
-            },
-            0x7D => Opcode {
-                instruction: Instruction::Adc,
-                mode: AddressingMode::AbsoluteX,
-                bytes: 3,
-                cycles: 4,
-            },
-            0x7E => Opcode {
-                instruction: Instruction::Ror,
-                mode: AddressingMode::AbsoluteX,
-                bytes: 3,
-                cycles: 7,
-            },
-            0x81 => Opcode {
-                instruction: Instruction::Sta,
-                mode: AddressingMode::IndexedIndirect,
-                bytes: 2,
-                cycles: 6,
-            },
-            0x84 => Opcode {
-                instruction: Instruction::Sty,
-                mode: AddressingMode::ZeroPage,
-                bytes: 2,
-                cycles: 3,
-            },
-            0x85 => Opcode {
-                instruction: Instruction::Sta,
-                mode: AddressingMode::ZeroPage,
-                bytes: 2,
-                cycles: 3,
-            },
-            0x86 => Opcode {
-                instruction: Instruction::Stx,
-                mode: AddressingMode::ZeroPage,
-                bytes: 2,
-                cycles: 3,
-            },
-            0x88 => Opcode {
-                instruction: Instruction::Dey,
-                mode: AddressingMode::Implicit,
-                bytes: 1,
-                cycles: 2,
-            },
-            0x8A => Opcode {
-                instruction: Instruction::Txa,
-                mode: AddressingMode::Implicit,
-                bytes: 1,
-                cycles: 2,
-            },
-            0x8C => Opcode {
-                instruction: Instruction::Sty,
-                mode: AddressingMode::Absolute,
-                bytes: 3,
-                cycles: 4,
-            },
-            0x8D => Opcode {
-                instruction: Instruction::Sta,
-                mode: AddressingMode::Absolute,
-                bytes: 3,
-                cycles: 4,
-            },
-            0x8E => Opcode {
-                instruction: Instruction::Stx,
-                mode: AddressingMode::Absolute,
-                bytes: 3,
-                cycles: 4,
-            },
-            0x90 => Opcode {
-                instruction: Instruction::Bcc,
-                mode: AddressingMode::Relative,
-                bytes: 2,
-                cycles: 2,
-            },
-            0x91 => Opcode {
-                instruction: Instruction::Sta,
-                mode: AddressingMode::IndirectIndexed,
-                bytes: 2,
-                cycles: 6,
-            },
-            0x94 => Opcode {
-                instruction: Instruction::Sty,
-                mode: AddressingMode::ZeroPageX,
-                bytes: 2,
-                cycles: 4,
-            },
-            0x95 => Opcode {
-                instruction: Instruction::Sta,
-                mode: AddressingMode::ZeroPageX,
-                bytes: 2,
-                cycles: 4,
-            },
-            0x96 => Opcode {
-                instruction: Instruction::Stx,
-                mode: AddressingMode::ZeroPageY,
-                bytes: 2,
-                cycles: 4,
-            },
-            0x98 => Opcode {
-                instruction: Instruction::Tya,
-                mode: AddressingMode::Implicit,
-                bytes: 1,
-                cycles: 2,
-            },
-            0x99 => Opcode {
-                instruction: Instruction::Sta,
-                mode: AddressingMode::AbsoluteY,
-                bytes: 3,
-                cycles: 5,
-            },
-            0x9A => Opcode {
-                instruction: Instruction::Txs,
-                mode: AddressingMode::Implicit,
-                bytes: 1,
-                cycles: 2,
-            },
-            0x9D => Opcode {
-                instruction: Instruction::Sta,
-                mode: AddressingMode::AbsoluteX,
-                bytes: 3,
-                cycles: 5,
-            },
-            0xA0 => Opcode {
-                instruction: Instruction::Ldy,
-                mode: AddressingMode::Immediate,
-                bytes: 2,
-                cycles: 2,
-            },
-            0xA1 => Opcode {
-                instruction: Instruction::Lda,
-                mode: AddressingMode::IndexedIndirect,
-                bytes: 2,
-                cycles: 6,
-            },
-            0xA2 => Opcode {
-                instruction: Instruction::Ldx,
-                mode: AddressingMode::Immediate,
-                bytes: 2,
-                cycles: 2,
-            },
-            0xA4 => Opcode {
-                instruction: Instruction::Ldy,
-                mode: AddressingMode::ZeroPage,
-                bytes: 2,
-                cycles: 3,
-            },
-            0xA5 => Opcode {
-                instruction: Instruction::Lda,
-                mode: AddressingMode::ZeroPage,
-                bytes: 2,
-                cycles: 3,
-            },
-            0xA6 => Opcode {
-                instruction: Instruction::Ldx,
-                mode: AddressingMode::ZeroPage,
-                bytes: 2,
-                cycles: 3,
-            },
-            0xA8 => Opcode {
-                instruction: Instruction::Tay,
-                mode: AddressingMode::Implicit,
-                bytes: 1,
-                cycles: 2,
-            },
-            0xA9 => Opcode {
-                instruction: Instruction::Lda,
-                mode: AddressingMode::Absolute,
-                bytes: 2,
-                cycles: 2,
-            },
-            0xAA => Opcode {
-                instruction: Instruction::Tax,
-                mode: AddressingMode::Implicit,
-                bytes: 1,
-                cycles: 2,
-            },
-            0xAC => Opcode {
-                instruction: Instruction::Ldy,
-                mode: AddressingMode::Absolute,
-                bytes: 3,
-                cycles: 4,
-            },
-            0xAD => Opcode {
-                instruction: Instruction::Lda,
-                mode: AddressingMode::Absolute,
-                bytes: 3,
-                cycles: 4,
-            },
-            0xAE => Opcode {
-                instruction: Instruction::Ldx,
-                mode: AddressingMode::Absolute,
-                bytes: 3,
-                cycles: 4,
-            },
-            0xB0 => Opcode {
-                instruction: Instruction::Bcs,
-                mode: AddressingMode::Relative,
-                bytes: 2,
-                cycles: 2,
-            },
-            0xB1 => Opcode {
-                instruction: Instruction::Lda,
-                mode: AddressingMode::IndirectIndexed,
-                bytes: 2,
-                cycles: 5,
-            },
-            0xB4 => Opcode {
-                instruction: Instruction::Ldy,
-                mode: AddressingMode::ZeroPageX,
-                bytes: 2,
-                cycles: 4,
-            },
-            0xB5 => Opcode {
-                instruction: Instruction::Lda,
-                mode: AddressingMode::ZeroPage,
-                bytes: 2,
-                cycles: 4,
-            },
-            0xB6 => Opcode {
-                instruction: Instruction::Ldx,
-                mode: AddressingMode::ZeroPageY,
-                bytes: 2,
-                cycles: 4,
-            },
-            0xB8 => Opcode {
-                instruction: Instruction::Clv,
-                mode: AddressingMode::Implicit,
-                bytes: 1,
-                cycles: 2,
-            },
-            0xB9 => Opcode {
-                instruction: Instruction::Lda,
-                mode: AddressingMode::AbsoluteY,
-                bytes: 3,
-                cycles: 4,
-            },
-            0xBA => Opcode {
-                instruction: Instruction::Tsx,
-                mode: AddressingMode::Implicit,
-                bytes: 1,
-                cycles: 2,
-            },
-            0xBC => Opcode {
-                instruction: Instruction::Ldy,
-                mode: AddressingMode::AbsoluteX,
-                bytes: 3,
-                cycles: 4,
-            },
-            0xBD => Opcode {
-                instruction: Instruction::Lda,
-                mode: AddressingMode::AbsoluteX,
-                bytes: 3,
-                cycles: 4,
-            },
-            0xBE => Opcode {
-                instruction: Instruction::Ldx,
-                mode: AddressingMode::AbsoluteY,
-                bytes: 3,
-                cycles: 4,
-            },
-            0xC0 => Opcode {
-                instruction: Instruction::Cpy,
-                mode: AddressingMode::Immediate,
-                bytes: 2,
-                cycles: 2,
-            },
-            0xC1 => Opcode {
-                instruction: Instruction::Cmp,
-                mode: AddressingMode::IndexedIndirect,
-                bytes: 2,
-                cycles: 6,
-            },
-            0xC4 => Opcode {
-                instruction: Instruction::Cpy,
-                mode: AddressingMode::ZeroPage,
-                bytes: 2,
-                cycles: 3,
-            },
-            0xC5 => Opcode {
-                instruction: Instruction::Cmp,
-                mode: AddressingMode::ZeroPage,
-                bytes: 2,
-                cycles: 3,
-            },
-            0xC6 => Opcode {
-                instruction: Instruction::Dec,
-                mode: AddressingMode::ZeroPage,
-                bytes: 2,
-                cycles: 5,
-            },
-            0xC8 => Opcode {
-                instruction: Instruction::Iny,
-                mode: AddressingMode::Implicit,
-                bytes: 1,
-                cycles: 2,
-            },
-            0xC9 => Opcode {
-                instruction: Instruction::Cmp,
-                mode: AddressingMode::Immediate,
-                bytes: 2,
-                cycles: 2,
-            },
-            0xCA => Opcode {
-                instruction: Instruction::Dex,
-                mode: AddressingMode::Implicit,
-                bytes: 1,
-                cycles: 2,
-            },
-            0xCC => Opcode {
-                instruction: Instruction::Cpy,
-                mode: AddressingMode::Absolute,
-                bytes: 3,
-                cycles: 4,
-            },
-            0xCD => Opcode {
-                instruction: Instruction::Cmp,
-                mode: AddressingMode::Absolute,
-                bytes: 3,
-                cycles: 4,
-            },
-            0xCE => Opcode {
-                instruction: Instruction::Dec,
-                mode: AddressingMode::Absolute,
-                bytes: 3,
-                cycles: 6,
-            },
-            0xD0 => Opcode {
-                instruction: Instruction::Bne,
-                mode: AddressingMode::Relative,
-                bytes: 2,
-                cycles: 2,
-            },
-            0xD1 => Opcode {
-                instruction: Instruction::Cmp,
-                mode: AddressingMode::IndirectIndexed,
-                bytes: 2,
-                cycles: 5,
-            },
-            0xD5 => Opcode {
-                instruction: Instruction::Cmp,
-                mode: AddressingMode::ZeroPageX,
-                bytes: 2,
-                cycles: 4,
-            },
-            0xD6 => Opcode {
-                instruction: Instruction::Dec,
-                mode: AddressingMode::ZeroPageX,
-                bytes: 2,
-                cycles: 6,
-            },
-            0xD8 => Opcode {
-                instruction: Instruction::Cld,
-                mode: AddressingMode::Implicit,
-                bytes: 1,
-                cycles: 2,
-            },
-            0xD9 => Opcode {
-                instruction: Instruction::Cmp,
-                mode: AddressingMode::AbsoluteY,
-                bytes: 3,
-                cycles: 4,
-            },
-            0xDD => Opcode {
-                instruction: Instruction::Cmp,
-                mode: AddressingMode::AbsoluteX,
-                bytes: 3,
-                cycles: 4,
-            },
-            0xDE => Opcode {
-                instruction: Instruction::Dec,
-                mode: AddressingMode::AbsoluteX,
-                bytes: 3,
-                cycles: 7,
-            },
-            0xE0 => Opcode {
-                instruction: Instruction::Cpx,
-                mode: AddressingMode::Immediate,
-                bytes: 2,
-                cycles: 2,
-            },
-            0xE1 => Opcode {
-                instruction: Instruction::Sbc,
-                mode: AddressingMode::IndexedIndirect,
-                bytes: 2,
-                cycles: 6,
-            },
-            0xE4 => Opcode {
-                instruction: Instruction::Cpx,
-                mode: AddressingMode::ZeroPage,
-                bytes: 2,
-                cycles: 3,
-            },
-            0xE5 => Opcode {
-                instruction: Instruction::Sbc,
-                mode: AddressingMode::ZeroPage,
-                bytes: 2,
-                cycles: 3,
-            },
-            0xE6 => Opcode {
-                instruction: Instruction::Inc,
-                mode: AddressingMode::ZeroPage,
-                bytes: 2,
-                cycles: 5,
-            },
-            0xE8 => Opcode {
-                instruction: Instruction::Inx,
-                mode: AddressingMode::Implicit,
-                bytes: 1,
-                cycles: 2,
-            },
-            0xE9 => Opcode {
-                instruction: Instruction::Sbc,
-                mode: AddressingMode::Immediate,
-                bytes: 2,
-                cycles: 2,
-            },
-            0xEA => Opcode {
-                instruction: Instruction::Nop,
-                mode: AddressingMode::Implicit,
-                bytes: 1,
-                cycles: 2,
-            },
-            0xEC => Opcode {
-                instruction: Instruction::Cpx,
-                mode: AddressingMode::Absolute,
-                bytes: 3,
-                cycles: 4,
-            },
-            0xED => Opcode {
-                instruction: Instruction::Sbc,
-                mode: AddressingMode::Absolute,
-                bytes: 3,
-                cycles: 4,
-            },
-            0xEE => Opcode {
-                instruction: Instruction::Inc,
-                mode: AddressingMode::Absolute,
-                bytes: 3,
-                cycles: 6,
-            },
-            0xF0 => Opcode {
-                instruction: Instruction::Beq,
-                mode: AddressingMode::Relative,
-                bytes: 2,
-                cycles: 2,
-            },
-            0xF1 => Opcode {
-                instruction: Instruction::Sbc,
-                mode: AddressingMode::IndirectIndexed,
-                bytes: 2,
-                cycles: 5,
-            },
-            0xF5 => Opcode {
-                instruction: Instruction::Sbc,
-                mode: AddressingMode::ZeroPageX,
-                bytes: 2,
-                cycles: 4,
-            },
-            0xF6 => Opcode {
-                instruction: Instruction::Inc,
-                mode: AddressingMode::ZeroPageX,
-                bytes: 2,
-                cycles: 6,
-            },
-            0xF8 => Opcode {
-                instruction: Instruction::Sed,
-                mode: AddressingMode::Implicit,
-                bytes: 1,
-                cycles: 2,
-            },
-            0xF9 => Opcode {
-                instruction: Instruction::Sbc,
-                mode: AddressingMode::AbsoluteY,
-                bytes: 3,
-                cycles: 4,
-            },
-            0xFD => Opcode {
-                instruction: Instruction::Sbc,
-                mode: AddressingMode::AbsoluteX,
-                bytes: 3,
-                cycles: 4,
-            },
-            0xFE => Opcode {
-                instruction: Instruction::Inc,
-                mode: AddressingMode::AbsoluteX,
-                bytes: 3,
-                cycles: 7,
-            },
-            _ => todo!(),
+    /// Chooses when the run calls return.
+    pub fn set_stop_conditions(&mut self, conditions: StopConditions) {
+        self.stop_conditions = conditions;
+    }
+
+    /// Calls `callback` for every real bus access of `kind` within `range`.
+    #[cfg(feature = "alloc")]
+    pub fn add_watch<F>(
+        &mut self,
+        kind: AccessKind,
+        range: RangeInclusive<u16>,
+        callback: F,
+    ) -> WatchId
+    where
+        F: FnMut(&MemoryAccess) + Send + 'static,
+    {
+        self.watches.observe(kind, range, Box::new(callback))
+    }
+
+    /// Makes the run loop stop after the step that performs a matching access.
+    #[cfg(feature = "alloc")]
+    pub fn add_watchpoint(&mut self, kind: AccessKind, range: RangeInclusive<u16>) -> WatchId {
+        self.watches.stop(kind, range)
+    }
+
+    /// Removes a watch or watchpoint.
+    #[cfg(feature = "alloc")]
+    pub fn remove_watch(&mut self, id: WatchId) {
+        self.watches.remove(id);
+    }
+
+    /// Traces every following instruction through `tracer`.
+    #[cfg(feature = "std")]
+    pub fn set_tracer(&mut self, tracer: Tracer) {
+        self.tracer = Some(tracer);
+    }
+
+    /// The installed tracer, if any.
+    #[cfg(feature = "std")]
+    pub fn tracer(&self) -> Option<&Tracer> {
+        self.tracer.as_ref()
+    }
+
+    /// Removes the tracer and hands it back, for example to read its buffer.
+    #[cfg(feature = "std")]
+    pub fn take_tracer(&mut self) -> Option<Tracer> {
+        self.tracer.take()
+    }
+
+    /// The programmer-visible registers.
+    pub fn state(&self) -> CpuState {
+        CpuState {
+            accumulator: self.accumulator,
+            index_x: self.index_x,
+            index_y: self.index_y,
+            status: self.processor_status,
+            stack_pointer: self.stack_pointer,
+            program_counter: self.program_counter,
+        }
+    }
+
+    /// Replaces the registers. An instruction part way through `tick` is
+    /// abandoned, since it was started from the old registers.
+    pub fn set_state(&mut self, state: CpuState) {
+        self.replay = Replay::new();
+        self.load_state(state);
+    }
+
+    fn load_state(&mut self, state: CpuState) {
+        self.accumulator = state.accumulator;
+        self.index_x = state.index_x;
+        self.index_y = state.index_y;
+        self.processor_status = state.status;
+        self.stack_pointer = state.stack_pointer;
+        self.program_counter = state.program_counter;
+    }
+
+    /// A.
+    pub fn accumulator(&self) -> u8 {
+        self.accumulator
+    }
+
+    /// X.
+    pub fn index_x(&self) -> u8 {
+        self.index_x
+    }
+
+    /// Y.
+    pub fn index_y(&self) -> u8 {
+        self.index_y
+    }
+
+    /// SP.
+    pub fn stack_pointer(&self) -> u8 {
+        self.stack_pointer
+    }
+
+    /// PC.
+    pub fn program_counter(&self) -> u16 {
+        self.program_counter
+    }
+
+    /// Sets PC. Like `set_state`, abandons an instruction part way through
+    /// `tick`.
+    pub fn set_program_counter(&mut self, address: u16) {
+        self.replay = Replay::new();
+        self.program_counter = address;
+    }
+
+    /// Cycles run since the last power-on, or since creation.
+    pub fn cycles(&self) -> u64 {
+        self.cycles
+    }
+
+    /// P.
+    pub fn status(&self) -> StatusFlags {
+        self.processor_status
+    }
+
+    /// Borrows the bus.
+    pub fn bus(&self) -> &B {
+        &self.bus
+    }
+
+    /// Borrows the bus mutably. Accesses made through it do not take cycles.
+    pub fn bus_mut(&mut self) -> &mut B {
+        &mut self.bus
+    }
+
+    /// Clears the registers and the cycle count, then runs the reset
+    /// sequence, which takes SP down to $FD, sets I and loads PC from the
+    /// RESET vector in seven cycles.
+    pub fn power_on(&mut self) {
+        self.set_state(CpuState::default());
+        self.cycles = 0;
+        self.reset();
+    }
+
+    /// Runs the reset sequence. A, X and Y survive; SP drops by three as the
+    /// interrupt sequence runs with its writes suppressed. I is set, the
+    /// 65C02 also clears D, and PC is loaded from the RESET vector.
+    pub fn reset(&mut self) {
+        self.replay = Replay::new();
+        self.halted = false;
+        self.waiting = false;
+        self.stall = 0;
+        self.nmi_edge = false;
+        self.service_pending = false;
+        self.read(self.program_counter);
+        self.read(self.program_counter);
+        for _ in 0..3 {
+            self.read(0x0100 | self.stack_pointer as u16);
+            self.stack_pointer = self.stack_pointer.wrapping_sub(1);
+        }
+        self.processor_status.set_unused(true);
+        self.processor_status.set_interrupt_disable(true);
+        if self.variant == CpuVariant::Wdc65C02 {
+            self.processor_status.set_decimal(false);
+        }
+        self.program_counter = self.read_word(RESET_VECTOR);
+    }
+
+    /// Latches a falling edge on NMI, which stays pending until serviced.
+    pub fn nmi(&mut self) {
+        self.nmi_edge = true;
+    }
+
+    /// Drives the IRQ input. IRQ is level-triggered and is serviced for as
+    /// long as it is held and I is clear.
+    pub fn set_irq(&mut self, asserted: bool) {
+        self.irq_line = asserted;
+    }
+
+    /// Holds the CPU off the bus for `cycles` more cycles before its next
+    /// instruction or interrupt sequence, as pulling RDY low for DMA does.
+    /// Stalled cycles are counted by `cycles` but make no bus access, so a
+    /// bus that keeps time from its accesses has to catch up from the count.
+    pub fn stall(&mut self, cycles: u32) {
+        self.stall = self.stall.saturating_add(cycles);
+    }
+
+    /// Steps until a stop condition is met.
+    pub fn run(&mut self) -> StopReason {
+        self.run_until(|_| false)
+    }
+
+    /// Steps until at least `cycles` more cycles have run, or a stop
+    /// condition is met first.
+    pub fn run_for_cycles(&mut self, cycles: u64) -> StopReason {
+        let target = self.cycles.saturating_add(cycles);
+        match self.run_until(|cpu| cpu.cycles >= target) {
+            StopReason::Predicate => StopReason::CyclesElapsed,
+            reason => reason,
+        }
+    }
+
+    /// Steps until `predicate`, checked before each step, returns true or a
+    /// stop condition is met.
+    pub fn run_until<F: FnMut(&Self) -> bool>(&mut self, mut predicate: F) -> StopReason {
+        let conditions = self.stop_conditions;
+        let mut executed = 0;
+
+        loop {
+            if matches!(conditions.instruction_limit, Some(limit) if executed >= limit) {
+                return StopReason::InstructionLimit;
+            }
+            if predicate(self) {
+                return StopReason::Predicate;
+            }
+
+            let info = match self.step() {
+                Ok(info) => info,
+                Err(CpuError::Halted { pc, opcode }) => return StopReason::Halted { pc, opcode },
+                Err(error) => return StopReason::Error(error),
+            };
+            executed += 1;
+
+            #[cfg(feature = "alloc")]
+            if let Some(access) = self.watches.take_hit() {
+                return StopReason::Watchpoint(access);
+            }
+
+            if conditions.on_break
+                && info.interrupt.is_none()
+                && self.decode(info.opcode).instruction == Instruction::Brk
+            {
+                return StopReason::Break { pc: info.pc };
+            }
+            if conditions.on_self_jump && !self.waiting && self.program_counter == info.pc {
+                return StopReason::SelfJump { pc: info.pc };
+            }
+            if conditions.breakpoint == Some(self.program_counter) {
+                return StopReason::Breakpoint {
+                    pc: self.program_counter,
+                };
+            }
+        }
+    }
+
+    /// Runs one whole instruction, or the interrupt sequence in its place.
+    /// An instruction part way through `tick` is finished instead. Pending
+    /// stall cycles are spent first and are not included in the summary.
+    pub fn step(&mut self) -> Result<StepInfo, CpuError> {
+        if self.replay.snapshot.is_some() {
+            loop {
+                if let Some(info) = self.tick()? {
+                    return Ok(info);
+                }
+            }
+        }
+
+        self.cycles += self.stall as u64;
+        self.stall = 0;
+        self.instruction()
+    }
+
+    /// Runs one cycle of the current instruction and returns its summary once
+    /// the final cycle has run. Each cycle is one bus access, except while
+    /// stalled or waiting in WAI, when a tick only counts a cycle.
+    pub fn tick(&mut self) -> Result<Option<StepInfo>, CpuError> {
+        if self.replay.snapshot.is_none() && self.stall > 0 {
+            self.stall -= 1;
+            self.cycles += 1;
+            return Ok(None);
+        }
+
+        let snapshot = match self.replay.snapshot {
+            Some(snapshot) => snapshot,
+            None => {
+                let snapshot = self.snapshot();
+                self.replay.snapshot = Some(snapshot);
+                self.replay.performed = 0;
+                snapshot
+            }
+        };
+
+        self.restore(&snapshot);
+        self.replay.cursor = 0;
+        self.replay.advanced = false;
+        self.replay.incomplete = false;
+        self.replay.active = true;
+        let result = self.instruction();
+        self.replay.active = false;
+
+        if self.replay.incomplete {
+            self.restore(&snapshot);
+            self.cycles += self.replay.performed as u64;
+            return Ok(None);
+        }
+
+        self.replay.snapshot = None;
+        result.map(Some)
+    }
+
+    fn instruction(&mut self) -> Result<StepInfo, CpuError> {
+        self.nmi_taken = false;
+
+        // A waiting CPU is still inside the WAI that precedes the PC. Any
+        // interrupt wakes it, but a masked IRQ just resumes execution.
+        if self.waiting {
+            if self.nmi_edge || self.irq_line {
+                self.waiting = false;
+                self.service_pending = self.nmi_edge || !self.processor_status.interrupt_disable();
+            }
+            self.cycles += 1;
+            return Ok(StepInfo {
+                pc: self.program_counter.wrapping_sub(1),
+                opcode: WAI_OPCODE,
+                cycles: 1,
+                interrupt: None,
+            });
+        }
+
+        let pc = self.program_counter;
+        let start = self.cycles;
+
+        if self.service_pending && !self.halted {
+            return Ok(self.service_interrupt());
+        }
+
+        if self.halted {
+            let byte = self.read(pc);
+            return Err(CpuError::Halted { pc, opcode: byte });
+        }
+
+        #[cfg(feature = "std")]
+        self.trace_instruction();
+
+        let byte = self.read_as(AccessKind::Execute, pc);
+        self.program_counter = pc.wrapping_add(1);
+
+        let opcode = self.decode(byte);
+        if !opcode.documented && !self.undocumented_opcodes {
+            self.program_counter = pc;
+            return Err(CpuError::IllegalOpcode { pc, opcode: byte });
+        }
+
+        self.execute(opcode);
+
+        if self.replay.incomplete {
+            return Ok(StepInfo {
+                pc,
+                opcode: byte,
+                cycles: 0,
+                interrupt: None,
+            });
+        }
+
+        // Like the hardware interrupt sequence, BRK does not poll.
+        if opcode.instruction == Instruction::Brk {
+            self.finish_interrupt_sequence();
+        } else {
+            self.service_pending = self.poll;
+        }
+
+        if self.halted {
+            return Err(CpuError::Halted { pc, opcode: byte });
+        }
+
+        if let Some(address) = self.bus.take_fault() {
+            return Err(CpuError::BusFault {
+                pc,
+                opcode: byte,
+                address,
+            });
         }
+
+        Ok(StepInfo {
+            pc,
+            opcode: byte,
+            cycles: (self.cycles - start) as u8,
+            interrupt: None,
+        })
     }
 
-    fn execute(&mut self, opcode: Opcode) {
-        let operand = match opcode.mode {
-            AddressingMode::Implicit => 0,
-            AddressingMode::Immediate => {
-                self.program_counter += 1;
-                self.memory[self.program_counter as usize]
+    // The hardware sequence is BRK with the PC increment suppressed: two reads
+    // of the next opcode, then the pushes and the vector fetch.
+    fn service_interrupt(&mut self) -> StepInfo {
+        let pc = self.program_counter;
+        let start = self.cycles;
+
+        self.read(pc);
+        self.read(pc);
+        self.interrupt(IRQ_VECTOR, false);
+
+        if !self.replay.incomplete {
+            self.finish_interrupt_sequence();
+        }
+
+        StepInfo {
+            pc,
+            opcode: 0x00,
+            cycles: (self.cycles - start) as u8,
+            interrupt: Some(if self.nmi_taken {
+                Interrupt::Nmi
+            } else {
+                Interrupt::Irq
+            }),
+        }
+    }
+
+    // Replayed ticks re-run the instruction from its start, so only the
+    // first tick of an instruction is traced.
+    #[cfg(feature = "std")]
+    fn trace_instruction(&mut self) {
+        if self.replay.active && self.replay.performed > 0 {
+            return;
+        }
+
+        if let Some(mut tracer) = self.tracer.take() {
+            tracer.trace(self);
+            self.tracer = Some(tracer);
+        }
+    }
+
+    fn finish_interrupt_sequence(&mut self) {
+        self.service_pending = false;
+        if self.nmi_taken {
+            self.nmi_edge = false;
+        }
+    }
+
+    fn fetch(&mut self) -> u8 {
+        let value = self.read(self.program_counter);
+        self.program_counter = self.program_counter.wrapping_add(1);
+        value
+    }
+
+    fn fetch_word(&mut self) -> u16 {
+        let low = self.fetch();
+        let high = self.fetch();
+        u16::from_le_bytes([low, high])
+    }
+
+    fn read(&mut self, address: u16) -> u8 {
+        self.read_as(AccessKind::Read, address)
+    }
+
+    // Accesses answered from the replay log were already reported when they
+    // reached the bus, so only live accesses are passed to the watches.
+    fn read_as(&mut self, kind: AccessKind, address: u16) -> u8 {
+        let value = match self.claim_cycle() {
+            Slot::Live => self.bus.read(address),
+            Slot::Logged(cursor) => return self.replay.values[cursor],
+            Slot::Recorded(cursor) => {
+                let value = self.bus.read(address);
+                self.replay.values[cursor] = value;
+                value
             }
-            AddressingMode::ZeroPage => {
-                self.program_counter += 1;
-                self.memory[(self.program_counter % 0xFF) as usize]
+            Slot::Speculative => return 0,
+        };
+
+        self.notify(kind, address, value);
+        value
+    }
+
+    fn write(&mut self, address: u16, value: u8) {
+        match self.claim_cycle() {
+            Slot::Live | Slot::Recorded(_) => self.bus.write(address, value),
+            Slot::Logged(_) | Slot::Speculative => return,
+        }
+
+        self.notify(AccessKind::Write, address, value);
+    }
+
+    #[cfg(feature = "alloc")]
+    fn notify(&mut self, kind: AccessKind, address: u16, value: u8) {
+        if !self.watches.is_empty() {
+            self.watches.notify(MemoryAccess {
+                kind,
+                address,
+                value,
+                cycle: self.cycles - 1,
+            });
+        }
+    }
+
+    #[cfg(not(feature = "alloc"))]
+    fn notify(&mut self, _kind: AccessKind, _address: u16, _value: u8) {}
+
+    // Accounts for one bus cycle and samples the interrupt inputs at its
+    // start, which is where the CPU polls for the end of the previous cycle.
+    fn claim_cycle(&mut self) -> Slot {
+        let slot = if self.replay.active {
+            let cursor = self.replay.cursor;
+            self.replay.cursor += 1;
+
+            if cursor < self.replay.performed {
+                Slot::Logged(cursor)
+            } else if !self.replay.advanced {
+                self.replay.performed += 1;
+                self.replay.advanced = true;
+                Slot::Recorded(cursor)
+            } else {
+                self.replay.incomplete = true;
+                Slot::Speculative
             }
-            _ => todo!(),
+        } else {
+            Slot::Live
+        };
+
+        let signals = match slot {
+            Slot::Logged(cursor) => self.replay.signals[cursor],
+            _ => Signals {
+                nmi: self.nmi_edge,
+                irq: self.irq_line,
+            },
+        };
+
+        match slot {
+            Slot::Recorded(cursor) => self.replay.signals[cursor] = signals,
+            Slot::Speculative => return slot,
+            _ => {}
+        }
+
+        self.cycles += 1;
+        self.nmi_sampled = signals.nmi;
+        self.poll = signals.nmi || (signals.irq && !self.processor_status.interrupt_disable());
+        slot
+    }
+
+    fn snapshot(&self) -> Snapshot {
+        Snapshot {
+            state: self.state(),
+            cycles: self.cycles,
+            halted: self.halted,
+            waiting: self.waiting,
+            service_pending: self.service_pending,
+        }
+    }
+
+    fn restore(&mut self, snapshot: &Snapshot) {
+        self.load_state(snapshot.state);
+        self.cycles = snapshot.cycles;
+        self.halted = snapshot.halted;
+        self.waiting = snapshot.waiting;
+        self.service_pending = snapshot.service_pending;
+    }
+
+    fn decode(&mut self, address: u8) -> Opcode {
+        self.variant.opcodes()[address as usize]
+    }
+
+    fn execute(&mut self, opcode: Opcode) {
+        let address = match opcode.instruction {
+            Instruction::Jsr => 0,
+            _ => self.resolve_address(&opcode),
         };
 
         match opcode.instruction {
-            Instruction::Adc => self.add_with_carry(),
-            Instruction::And => self.logical_and(operand),
-            Instruction::Asl => self.arithmetic_shift_left(),
-            Instruction::Bcc => self.branch_if_carry_clear(operand),
-            Instruction::Bcs => self.branch_if_carry_set(operand),
-            Instruction::Beq => self.branch_if_equal(operand),
-            Instruction::Bit => self.bit_test(),
-            Instruction::Bmi => self.branch_if_minus(operand),
-            Instruction::Bne => self.branch_if_not_equal(operand),
-            Instruction::Bpl => self.branch_if_positive(operand),
+            Instruction::Adc => self.add_with_carry(address),
+            Instruction::And => self.logical_and(address),
+            Instruction::Asl => self.arithmetic_shift_left(&opcode.mode, address),
+            Instruction::Bcc => self.branch_if_carry_clear(address),
+            Instruction::Bcs => self.branch_if_carry_set(address),
+            Instruction::Beq => self.branch_if_equal(address),
+            Instruction::Bit => self.bit_test(&opcode.mode, address),
+            Instruction::Bmi => self.branch_if_minus(address),
+            Instruction::Bne => self.branch_if_not_equal(address),
+            Instruction::Bpl => self.branch_if_positive(address),
             Instruction::Brk => self.force_interrupt(),
-            Instruction::Bvc => self.branch_if_overflow_clear(operand),
-            Instruction::Bvs => self.branch_if_overflow_set(operand),
+            Instruction::Bvc => self.branch_if_overflow_clear(address),
+            Instruction::Bvs => self.branch_if_overflow_set(address),
             Instruction::Clc => self.clear_carry_flag(),
             Instruction::Cld => self.clear_decimal_mode(),
             Instruction::Cli => self.clear_interrupt_disable(),
             Instruction::Clv => self.clear_overflow_flag(),
-            Instruction::Cmp => self.compare(),
-            Instruction::Cpx => self.compare_x_register(),
-            Instruction::Cpy => self.compare_y_register(),
-            Instruction::Dec => self.decrement_memory(operand),
+            Instruction::Cmp => self.compare(address),
+            Instruction::Cpx => self.compare_x_register(address),
+            Instruction::Cpy => self.compare_y_register(address),
+            Instruction::Dec => self.decrement_memory(&opcode.mode, address),
             Instruction::Dex => self.decrement_x_register(),
             Instruction::Dey => self.decrement_y_register(),
-            Instruction::Eor => self.exclusive_or(operand),
-            Instruction::Inc => self.increment_memory(operand),
+            Instruction::Eor => self.exclusive_or(address),
+            Instruction::Inc => self.increment_memory(&opcode.mode, address),
             Instruction::Inx => self.increment_x_register(),
             Instruction::Iny => self.increment_y_register(),
-            Instruction::Jmp => self.jump(operand),
+            Instruction::Jmp => self.jump(address),
             Instruction::Jsr => self.jump_to_subroutine(),
-            Instruction::Lda => self.load_accumulator(operand),
-            Instruction::Ldx => self.load_x_register(operand),
-            Instruction::Ldy => self.load_y_register(operand),
-            Instruction::Lsr => self.logical_shift_right(),
-            Instruction::Nop => self.no_operation(),
-            Instruction::Ora => self.logical_inclusive_or(operand),
+            Instruction::Lda => self.load_accumulator(address),
+            Instruction::Ldx => self.load_x_register(address),
+            Instruction::Ldy => self.load_y_register(address),
+            Instruction::Lsr => self.logical_shift_right(&opcode.mode, address),
+            Instruction::Nop => self.no_operation(&opcode, address),
+            Instruction::Ora => self.logical_inclusive_or(address),
             Instruction::Pha => self.push_accumulator(),
             Instruction::Php => self.push_processor_status(),
             Instruction::Pla => self.pull_accumulator(),
             Instruction::Plp => self.pull_processor_status(),
-            Instruction::Rol => self.rotate_left(),
-            Instruction::Ror => self.rotate_right(),
+            Instruction::Rol => self.rotate_left(&opcode.mode, address),
+            Instruction::Ror => self.rotate_right(&opcode.mode, address),
             Instruction::Rti => self.return_from_interrupt(),
             Instruction::Rts => self.return_from_subroutine(),
-            Instruction::Sbc => self.subtract_with_carry(),
+            Instruction::Sbc => self.subtract_with_carry(address),
             Instruction::Sec => self.set_carry_flag(),
             Instruction::Sed => self.set_decimal_flag(),
             Instruction::Sei => self.set_interrupt_disable(),
-            Instruction::Sta => self.store_accumulator(operand),
-            Instruction::Stx => self.store_x_register(operand),
-            Instruction::Sty => self.store_y_register(operand),
+            Instruction::Sta => self.store_accumulator(address),
+            Instruction::Stx => self.store_x_register(address),
+            Instruction::Sty => self.store_y_register(address),
             Instruction::Tax => self.transfer_accumulator_to_x(),
             Instruction::Tay => self.transfer_accumulator_to_y(),
             Instruction::Tsx => self.transfer_stack_pointer_to_x(),
             Instruction::Txa => self.transfer_x_to_accumulator(),
             Instruction::Txs => self.transfer_x_to_stack_pointer(),
             Instruction::Tya => self.transfer_y_to_accumulator(),
+            Instruction::Alr => self.and_shift_right(address),
+            Instruction::Anc => self.and_carry(address),
+            Instruction::Arr => self.and_rotate_right(address),
+            Instruction::Dcp => self.decrement_compare(&opcode.mode, address),
+            Instruction::Isc => self.increment_subtract(&opcode.mode, address),
+            Instruction::Jam => self.halt(),
+            Instruction::Las => self.load_accumulator_x_stack_pointer(address),
+            Instruction::Lax => self.load_accumulator_x(address),
+            Instruction::Lxa => self.load_accumulator_x_unstable(address),
+            Instruction::Rla => self.rotate_left_and(&opcode.mode, address),
+            Instruction::Rra => self.rotate_right_add(&opcode.mode, address),
+            Instruction::Sax => self.store_accumulator_x(address),
+            Instruction::Sbx => self.subtract_x_register(address),
+            Instruction::Sha => {
+                self.store_high_byte_and(address, self.index_y, self.accumulator & self.index_x)
+            }
+            Instruction::Shx => self.store_high_byte_and(address, self.index_y, self.index_x),
+            Instruction::Shy => self.store_high_byte_and(address, self.index_x, self.index_y),
+            Instruction::Slo => self.shift_left_or(&opcode.mode, address),
+            Instruction::Sre => self.shift_right_exclusive_or(&opcode.mode, address),
+            Instruction::Tas => self.transfer_to_stack_pointer_store(address),
+            Instruction::Xaa => self.transfer_x_and_unstable(address),
+            Instruction::Bbr(bit) => self.branch_if_bit_reset(bit, address),
+            Instruction::Bbs(bit) => self.branch_if_bit_set(bit, address),
+            Instruction::Bra => self.branch_always(address),
+            Instruction::Phx => self.push_x_register(),
+            Instruction::Phy => self.push_y_register(),
+            Instruction::Plx => self.pull_x_register(),
+            Instruction::Ply => self.pull_y_register(),
+            Instruction::Rmb(bit) => self.reset_memory_bit(bit, &opcode.mode, address),
+            Instruction::Smb(bit) => self.set_memory_bit(bit, &opcode.mode, address),
+            Instruction::Stp => self.stop(),
+            Instruction::Stz => self.store_zero(address),
+            Instruction::Trb => self.test_and_reset_bits(&opcode.mode, address),
+            Instruction::Tsb => self.test_and_set_bits(&opcode.mode, address),
+            Instruction::Wai => self.wait_for_interrupt(),
+        }
+    }
+
+    fn resolve_address(&mut self, opcode: &Opcode) -> u16 {
+        let access = Self::access(&opcode.instruction);
+
+        match opcode.mode {
+            AddressingMode::Implicit | AddressingMode::Accumulator => {
+                // The 65C02's single-cycle NOPs skip the dummy operand read.
+                if opcode.cycles > 1 {
+                    self.read(self.program_counter);
+                }
+                0
+            }
+            AddressingMode::Immediate => {
+                let address = self.program_counter;
+                self.program_counter = self.program_counter.wrapping_add(1);
+                address
+            }
+            AddressingMode::Relative => self.fetch_relative(),
+            AddressingMode::ZeroPage | AddressingMode::ZeroPageRelative => self.fetch() as u16,
+            AddressingMode::ZeroPageX => self.zero_page_indexed(self.index_x),
+            AddressingMode::ZeroPageY => self.zero_page_indexed(self.index_y),
+            AddressingMode::Absolute => self.fetch_word(),
+            AddressingMode::AbsoluteX => {
+                let base = self.fetch_word();
+                self.indexed(&opcode.instruction, access, base, self.index_x)
+            }
+            AddressingMode::AbsoluteY => {
+                let base = self.fetch_word();
+                self.indexed(&opcode.instruction, access, base, self.index_y)
+            }
+            AddressingMode::Indirect => {
+                let pointer = self.fetch_word();
+                if self.variant == CpuVariant::Wdc65C02 {
+                    self.read(self.program_counter.wrapping_sub(1));
+                    self.read_word(pointer)
+                } else {
+                    self.read_word_within_page(pointer)
+                }
+            }
+            AddressingMode::IndexedIndirect => {
+                let base = self.fetch();
+                self.read(base as u16);
+                self.read_zero_page_word(base.wrapping_add(self.index_x))
+            }
+            AddressingMode::IndirectIndexed => {
+                let pointer = self.fetch();
+                let base = self.read_zero_page_word(pointer);
+                self.indexed(&opcode.instruction, access, base, self.index_y)
+            }
+            AddressingMode::ZeroPageIndirect => {
+                let pointer = self.fetch();
+                self.read_zero_page_word(pointer)
+            }
+            AddressingMode::AbsoluteIndexedIndirect => {
+                let base = self.fetch_word();
+                self.read(self.program_counter.wrapping_sub(1));
+                self.read_word(base.wrapping_add(self.index_x as u16))
+            }
+        }
+    }
+
+    fn access(instruction: &Instruction) -> Access {
+        match instruction {
+            Instruction::Sta
+            | Instruction::Stx
+            | Instruction::Sty
+            | Instruction::Stz
+            | Instruction::Sax
+            | Instruction::Sha
+            | Instruction::Shx
+            | Instruction::Shy
+            | Instruction::Tas => Access::Write,
+            Instruction::Asl
+            | Instruction::Lsr
+            | Instruction::Rol
+            | Instruction::Ror
+            | Instruction::Inc
+            | Instruction::Dec
+            | Instruction::Trb
+            | Instruction::Tsb
+            | Instruction::Rmb(_)
+            | Instruction::Smb(_)
+            | Instruction::Slo
+            | Instruction::Rla
+            | Instruction::Sre
+            | Instruction::Rra
+            | Instruction::Dcp
+            | Instruction::Isc => Access::Modify,
+            _ => Access::Read,
+        }
+    }
+
+    fn fetch_relative(&mut self) -> u16 {
+        let offset = self.fetch() as i8;
+        self.program_counter.wrapping_add(offset as u16)
+    }
+
+    fn zero_page_indexed(&mut self, index: u8) -> u16 {
+        let base = self.fetch();
+        self.read(base as u16);
+        base.wrapping_add(index) as u16
+    }
+
+    // Indexing reads from the unfixed address while the high byte is carried.
+    // Reads skip that cycle when no carry is needed, and so do 65C02 shifts;
+    // the 65C02 re-reads the last operand byte instead of the wrong address.
+    fn indexed(&mut self, instruction: &Instruction, access: Access, base: u16, index: u8) -> u16 {
+        let address = base.wrapping_add(index as u16);
+        let crossed = Self::crosses_page(base, address);
+        let cmos = self.variant == CpuVariant::Wdc65C02;
+        let shift = matches!(
+            instruction,
+            Instruction::Asl | Instruction::Lsr | Instruction::Rol | Instruction::Ror
+        );
+
+        if crossed || access == Access::Write || (access == Access::Modify && !(cmos && shift)) {
+            if cmos && crossed {
+                self.read(self.program_counter.wrapping_sub(1));
+            } else {
+                self.read((base & 0xFF00) | (address & 0x00FF));
+            }
+        }
+
+        address
+    }
+
+    fn crosses_page(from: u16, to: u16) -> bool {
+        from & 0xFF00 != to & 0xFF00
+    }
+
+    fn read_word(&mut self, address: u16) -> u16 {
+        let low = self.read(address);
+        let high = self.read(address.wrapping_add(1));
+        u16::from_le_bytes([low, high])
+    }
+
+    fn read_word_within_page(&mut self, address: u16) -> u16 {
+        let low = self.read(address);
+        let high = self.read((address & 0xFF00) | (address.wrapping_add(1) & 0x00FF));
+        u16::from_le_bytes([low, high])
+    }
+
+    fn read_zero_page_word(&mut self, address: u8) -> u16 {
+        let low = self.read(address as u16);
+        let high = self.read(address.wrapping_add(1) as u16);
+        u16::from_le_bytes([low, high])
+    }
+
+    // Read-modify-write instructions write the unmodified value back (NMOS)
+    // or read it again (65C02) before storing the result.
+    fn read_operand(&mut self, mode: &AddressingMode, address: u16) -> u8 {
+        match mode {
+            AddressingMode::Accumulator => self.accumulator,
+            _ => {
+                let value = self.read(address);
+                if self.variant == CpuVariant::Wdc65C02 {
+                    self.read(address);
+                } else {
+                    self.write(address, value);
+                }
+                value
+            }
+        }
+    }
+
+    fn write_operand(&mut self, mode: &AddressingMode, address: u16, value: u8) {
+        match mode {
+            AddressingMode::Accumulator => self.accumulator = value,
+            _ => self.write(address, value),
+        }
+    }
+
+    fn add_with_carry(&mut self, address: u16) {
+        let value = self.read(address);
+        self.add(value);
+    }
+
+    fn logical_and(&mut self, address: u16) {
+        self.accumulator &= self.read(address);
+        self.update_zero_flag(self.accumulator);
+        self.update_negative_flag(self.accumulator);
+    }
+
+    fn arithmetic_shift_left(&mut self, mode: &AddressingMode, address: u16) {
+        let value = self.read_operand(mode, address);
+        let result = value << 1;
+        self.write_operand(mode, address, result);
+        self.processor_status.set_carry(value & 0b1000_0000 != 0);
+        self.update_zero_flag(result);
+        self.update_negative_flag(result);
+    }
+
+    fn bit_test(&mut self, mode: &AddressingMode, address: u16) {
+        let value = self.read(address);
+        self.update_zero_flag(self.accumulator & value);
+        if let AddressingMode::Immediate = mode {
+            return;
+        }
+        self.processor_status.set_overflow(value & 0b0100_0000 != 0);
+        self.processor_status.set_negative(value & 0b1000_0000 != 0);
+    }
+
+    fn compare(&mut self, address: u16) {
+        let value = self.read(address);
+        self.compare_register(self.accumulator, value);
+    }
+
+    fn compare_x_register(&mut self, address: u16) {
+        let value = self.read(address);
+        self.compare_register(self.index_x, value);
+    }
+
+    fn compare_y_register(&mut self, address: u16) {
+        let value = self.read(address);
+        self.compare_register(self.index_y, value);
+    }
+
+    fn decrement_memory(&mut self, mode: &AddressingMode, address: u16) {
+        let value = self.read_operand(mode, address).wrapping_sub(1);
+        self.write_operand(mode, address, value);
+        self.update_zero_flag(value);
+        self.update_negative_flag(value);
+    }
+
+    fn exclusive_or(&mut self, address: u16) {
+        self.accumulator ^= self.read(address);
+        self.update_zero_flag(self.accumulator);
+        self.update_negative_flag(self.accumulator);
+    }
+
+    fn increment_memory(&mut self, mode: &AddressingMode, address: u16) {
+        let value = self.read_operand(mode, address).wrapping_add(1);
+        self.write_operand(mode, address, value);
+        self.update_zero_flag(value);
+        self.update_negative_flag(value);
+    }
+
+    fn jump(&mut self, address: u16) {
+        self.program_counter = address;
+    }
+
+    // JSR pushes the address of its own last byte, which RTS increments. The
+    // high byte of the target is fetched only after the push.
+    fn jump_to_subroutine(&mut self) {
+        let low = self.fetch();
+        self.read_stack();
+        self.push_word(self.program_counter);
+        let high = self.read(self.program_counter);
+        self.program_counter = u16::from_le_bytes([low, high]);
+    }
+
+    fn load_accumulator(&mut self, address: u16) {
+        self.accumulator = self.read(address);
+        self.update_zero_flag(self.accumulator);
+        self.update_negative_flag(self.accumulator);
+    }
+
+    fn load_x_register(&mut self, address: u16) {
+        self.index_x = self.read(address);
+        self.update_zero_flag(self.index_x);
+        self.update_negative_flag(self.index_x);
+    }
+
+    fn load_y_register(&mut self, address: u16) {
+        self.index_y = self.read(address);
+        self.update_zero_flag(self.index_y);
+        self.update_negative_flag(self.index_y);
+    }
+
+    fn logical_shift_right(&mut self, mode: &AddressingMode, address: u16) {
+        let value = self.read_operand(mode, address);
+        let result = value >> 1;
+        self.write_operand(mode, address, result);
+        self.processor_status.set_carry(value & 0b0000_0001 != 0);
+        self.update_zero_flag(result);
+        self.update_negative_flag(result);
+    }
+
+    fn logical_inclusive_or(&mut self, address: u16) {
+        self.accumulator |= self.read(address);
+        self.update_zero_flag(self.accumulator);
+        self.update_negative_flag(self.accumulator);
+    }
+
+    fn push_accumulator(&mut self) {
+        self.push(self.accumulator);
+    }
+
+    fn push_processor_status(&mut self) {
+        self.push(self.processor_status.to_pushed(true));
+    }
+
+    fn pull_accumulator(&mut self) {
+        self.read_stack();
+        self.accumulator = self.pull();
+        self.update_zero_flag(self.accumulator);
+        self.update_negative_flag(self.accumulator);
+    }
+
+    fn pull_processor_status(&mut self) {
+        self.read_stack();
+        let status = self.pull();
+        self.processor_status = StatusFlags::from_pulled(status);
+    }
+
+    fn rotate_left(&mut self, mode: &AddressingMode, address: u16) {
+        let value = self.read_operand(mode, address);
+        let result = (value << 1) | self.processor_status.carry() as u8;
+        self.write_operand(mode, address, result);
+        self.processor_status.set_carry(value & 0b1000_0000 != 0);
+        self.update_zero_flag(result);
+        self.update_negative_flag(result);
+    }
+
+    fn rotate_right(&mut self, mode: &AddressingMode, address: u16) {
+        let value = self.read_operand(mode, address);
+        let result = (value >> 1) | ((self.processor_status.carry() as u8) << 7);
+        self.write_operand(mode, address, result);
+        self.processor_status.set_carry(value & 0b0000_0001 != 0);
+        self.update_zero_flag(result);
+        self.update_negative_flag(result);
+    }
+
+    fn return_from_interrupt(&mut self) {
+        self.read_stack();
+        let status = self.pull();
+        self.processor_status = StatusFlags::from_pulled(status);
+        self.program_counter = self.pull_word();
+    }
+
+    fn return_from_subroutine(&mut self) {
+        self.read_stack();
+        let address = self.pull_word();
+        self.read(address);
+        self.program_counter = address.wrapping_add(1);
+    }
+
+    fn subtract_with_carry(&mut self, address: u16) {
+        let value = self.read(address);
+        self.subtract(value);
+    }
+
+    fn store_accumulator(&mut self, address: u16) {
+        self.write(address, self.accumulator);
+    }
+
+    fn store_x_register(&mut self, address: u16) {
+        self.write(address, self.index_x);
+    }
+
+    fn store_y_register(&mut self, address: u16) {
+        self.write(address, self.index_y);
+    }
+
+    fn decrement_x_register(&mut self) {
+        self.index_x = self.index_x.wrapping_sub(1);
+        self.update_zero_flag(self.index_x);
+        self.update_negative_flag(self.index_x);
+    }
+
+    fn decrement_y_register(&mut self) {
+        self.index_y = self.index_y.wrapping_sub(1);
+        self.update_zero_flag(self.index_y);
+        self.update_negative_flag(self.index_y);
+    }
+
+    fn increment_x_register(&mut self) {
+        self.index_x = self.index_x.wrapping_add(1);
+        self.update_zero_flag(self.index_x);
+        self.update_negative_flag(self.index_x);
+    }
+
+    fn increment_y_register(&mut self) {
+        self.index_y = self.index_y.wrapping_add(1);
+        self.update_zero_flag(self.index_y);
+        self.update_negative_flag(self.index_y);
+    }
+
+    fn force_interrupt(&mut self) {
+        self.program_counter = self.program_counter.wrapping_add(1);
+        self.interrupt(IRQ_VECTOR, true);
+    }
+
+    fn clear_carry_flag(&mut self) {
+        self.processor_status.set_carry(false);
+    }
+
+    fn clear_decimal_mode(&mut self) {
+        self.processor_status.set_decimal(false);
+    }
+
+    fn clear_interrupt_disable(&mut self) {
+        self.processor_status.set_interrupt_disable(false);
+    }
+
+    fn clear_overflow_flag(&mut self) {
+        self.processor_status.set_overflow(false);
+    }
+
+    // The 65C02's eight-cycle NOP keeps reading after its operand.
+    fn no_operation(&mut self, opcode: &Opcode, address: u16) {
+        if let AddressingMode::Implicit = opcode.mode {
+            return;
+        }
+
+        self.read(address);
+        for _ in 4..opcode.cycles {
+            self.read(address);
         }
+    }
+
+    fn set_carry_flag(&mut self) {
+        self.processor_status.set_carry(true);
+    }
+
+    fn set_decimal_flag(&mut self) {
+        self.processor_status.set_decimal(true);
+    }
+
+    fn set_interrupt_disable(&mut self) {
+        self.processor_status.set_interrupt_disable(true);
+    }
+
+    fn transfer_accumulator_to_x(&mut self) {
+        self.index_x = self.accumulator;
+        self.update_zero_flag(self.index_x);
+        self.update_negative_flag(self.index_x);
+    }
+
+    fn transfer_accumulator_to_y(&mut self) {
+        self.index_y = self.accumulator;
+        self.update_zero_flag(self.index_y);
+        self.update_negative_flag(self.index_y);
+    }
+
+    fn transfer_stack_pointer_to_x(&mut self) {
+        self.index_x = self.stack_pointer;
+        self.update_zero_flag(self.index_x);
+        self.update_negative_flag(self.index_x);
+    }
 
-        self.program_counter += 1;
+    fn transfer_x_to_accumulator(&mut self) {
+        self.accumulator = self.index_x;
+        self.update_zero_flag(self.accumulator);
+        self.update_negative_flag(self.accumulator);
     }
 
-    fn add_with_carry(&mut self) {
-        todo!();
+    fn transfer_x_to_stack_pointer(&mut self) {
+        self.stack_pointer = self.index_x;
     }
 
-    fn logical_and(&mut self, operand: u8) {
-        self.accumulator &= operand;
+    fn transfer_y_to_accumulator(&mut self) {
+        self.accumulator = self.index_y;
         self.update_zero_flag(self.accumulator);
         self.update_negative_flag(self.accumulator);
     }
 
-    fn arithmetic_shift_left(&mut self) {
-        todo!();
+    fn and_shift_right(&mut self, address: u16) {
+        self.logical_and(address);
+        self.logical_shift_right(&AddressingMode::Accumulator, 0);
+    }
+
+    fn and_carry(&mut self, address: u16) {
+        self.logical_and(address);
+        self.processor_status
+            .set_carry(self.accumulator & 0b1000_0000 != 0);
+    }
+
+    fn and_rotate_right(&mut self, address: u16) {
+        let value = self.accumulator & self.read(address);
+        let carry = self.processor_status.carry() as u8;
+        let mut result = (value >> 1) | (carry << 7);
+
+        if self.decimal_mode_enabled() {
+            self.update_negative_flag(result);
+            self.update_zero_flag(result);
+            self.processor_status
+                .set_overflow((value ^ result) & 0b0100_0000 != 0);
+            if (value & 0x0F) + (value & 0x01) > 0x05 {
+                result = (result & 0xF0) | (result.wrapping_add(0x06) & 0x0F);
+            }
+            let high_carry = (value as u16 & 0xF0) + (value as u16 & 0x10) > 0x50;
+            if high_carry {
+                result = result.wrapping_add(0x60);
+            }
+            self.processor_status.set_carry(high_carry);
+        } else {
+            self.update_negative_flag(result);
+            self.update_zero_flag(result);
+            self.processor_status.set_carry(result & 0b0100_0000 != 0);
+            self.processor_status
+                .set_overflow(((result >> 6) ^ (result >> 5)) & 0b0000_0001 != 0);
+        }
+
+        self.accumulator = result;
+    }
+
+    fn decrement_compare(&mut self, mode: &AddressingMode, address: u16) {
+        let value = self.read_operand(mode, address).wrapping_sub(1);
+        self.write_operand(mode, address, value);
+        self.compare_register(self.accumulator, value);
     }
 
-    fn bit_test(&mut self) {
-        todo!();
+    fn increment_subtract(&mut self, mode: &AddressingMode, address: u16) {
+        let value = self.read_operand(mode, address).wrapping_add(1);
+        self.write_operand(mode, address, value);
+        self.subtract(value);
     }
 
-    fn compare(&mut self) {
-        todo!();
+    // The CPU stays jammed on the offending opcode until it is reset.
+    fn halt(&mut self) {
+        self.halted = true;
+        self.program_counter = self.program_counter.wrapping_sub(1);
     }
 
-    fn compare_x_register(&mut self) {
-        todo!();
+    fn load_accumulator_x_stack_pointer(&mut self, address: u16) {
+        let value = self.read(address) & self.stack_pointer;
+        self.accumulator = value;
+        self.index_x = value;
+        self.stack_pointer = value;
+        self.update_zero_flag(value);
+        self.update_negative_flag(value);
     }
 
-    fn compare_y_register(&mut self) {
-        todo!();
+    fn load_accumulator_x(&mut self, address: u16) {
+        self.load_accumulator(address);
+        self.index_x = self.accumulator;
     }
 
-    fn decrement_memory(&mut self, operand: u8) {
-        self.memory[operand as usize] -= 1;
-        self.update_zero_flag(self.memory[operand as usize]);
-        self.update_negative_flag(self.memory[operand as usize]);
+    fn load_accumulator_x_unstable(&mut self, address: u16) {
+        let value = (self.accumulator | UNSTABLE_MAGIC) & self.read(address);
+        self.accumulator = value;
+        self.index_x = value;
+        self.update_zero_flag(value);
+        self.update_negative_flag(value);
     }
 
-    fn exclusive_or(&mut self, operand: u8) {
-        self.accumulator ^= self.memory[operand as usize];
+    fn rotate_left_and(&mut self, mode: &AddressingMode, address: u16) {
+        let value = self.read_operand(mode, address);
+        let result = (value << 1) | self.processor_status.carry() as u8;
+        self.write_operand(mode, address, result);
+        self.processor_status.set_carry(value & 0b1000_0000 != 0);
+        self.accumulator &= result;
         self.update_zero_flag(self.accumulator);
         self.update_negative_flag(self.accumulator);
     }
 
-    fn increment_memory(&mut self, operand: u8) {
-        self.memory[operand as usize] += 1;
-        self.update_zero_flag(self.memory[operand as usize]);
-        self.update_negative_flag(self.memory[operand as usize]);
+    fn rotate_right_add(&mut self, mode: &AddressingMode, address: u16) {
+        let value = self.read_operand(mode, address);
+        let result = (value >> 1) | ((self.processor_status.carry() as u8) << 7);
+        self.write_operand(mode, address, result);
+        self.processor_status.set_carry(value & 0b0000_0001 != 0);
+        self.add(result);
     }
 
-    fn jump(&mut self, operand: u8) {
-        self.program_counter = operand as u16;
+    fn store_accumulator_x(&mut self, address: u16) {
+        self.write(address, self.accumulator & self.index_x);
     }
 
-    fn jump_to_subroutine(&mut self) {
-        todo!();
+    fn subtract_x_register(&mut self, address: u16) {
+        let value = self.read(address);
+        let register = self.accumulator & self.index_x;
+        self.index_x = register.wrapping_sub(value);
+        self.processor_status.set_carry(register >= value);
+        self.update_zero_flag(self.index_x);
+        self.update_negative_flag(self.index_x);
+    }
+
+    // SHA, SHX, SHY and TAS store the value ANDed with the high byte of the
+    // base address plus one; a page cross replaces the high byte of the
+    // target address with that same value.
+    fn store_high_byte_and(&mut self, address: u16, index: u8, value: u8) {
+        let base = address.wrapping_sub(index as u16);
+        let result = value & ((base >> 8) as u8).wrapping_add(1);
+
+        if Self::crosses_page(base, address) {
+            self.write(((result as u16) << 8) | (address & 0x00FF), result);
+        } else {
+            self.write(address, result);
+        }
+    }
+
+    fn shift_left_or(&mut self, mode: &AddressingMode, address: u16) {
+        let value = self.read_operand(mode, address);
+        let result = value << 1;
+        self.write_operand(mode, address, result);
+        self.processor_status.set_carry(value & 0b1000_0000 != 0);
+        self.accumulator |= result;
+        self.update_zero_flag(self.accumulator);
+        self.update_negative_flag(self.accumulator);
     }
 
-    fn load_accumulator(&mut self, operand: u8) {
-        self.accumulator = self.memory[operand as usize];
+    fn shift_right_exclusive_or(&mut self, mode: &AddressingMode, address: u16) {
+        let value = self.read_operand(mode, address);
+        let result = value >> 1;
+        self.write_operand(mode, address, result);
+        self.processor_status.set_carry(value & 0b0000_0001 != 0);
+        self.accumulator ^= result;
         self.update_zero_flag(self.accumulator);
         self.update_negative_flag(self.accumulator);
     }
 
-    fn load_x_register(&mut self, operand: u8) {
-        self.index_x = self.memory[operand as usize];
+    fn transfer_to_stack_pointer_store(&mut self, address: u16) {
+        self.stack_pointer = self.accumulator & self.index_x;
+        self.store_high_byte_and(address, self.index_y, self.stack_pointer);
+    }
+
+    fn transfer_x_and_unstable(&mut self, address: u16) {
+        self.accumulator = (self.accumulator | UNSTABLE_MAGIC) & self.index_x & self.read(address);
+        self.update_zero_flag(self.accumulator);
+        self.update_negative_flag(self.accumulator);
+    }
+
+    fn branch_if_bit_reset(&mut self, bit: u8, address: u16) {
+        let value = self.read(address);
+        self.read(address);
+        let target = self.fetch_relative();
+        self.branch(value & (1 << bit) == 0, target);
+    }
+
+    fn branch_if_bit_set(&mut self, bit: u8, address: u16) {
+        let value = self.read(address);
+        self.read(address);
+        let target = self.fetch_relative();
+        self.branch(value & (1 << bit) != 0, target);
+    }
+
+    fn branch_always(&mut self, address: u16) {
+        self.branch(true, address);
+    }
+
+    fn push_x_register(&mut self) {
+        self.push(self.index_x);
+    }
+
+    fn push_y_register(&mut self) {
+        self.push(self.index_y);
+    }
+
+    fn pull_x_register(&mut self) {
+        self.read_stack();
+        self.index_x = self.pull();
         self.update_zero_flag(self.index_x);
         self.update_negative_flag(self.index_x);
     }
 
-    fn load_y_register(&mut self, operand: u8) {
-        self.index_y = self.memory[operand as usize];
+    fn pull_y_register(&mut self) {
+        self.read_stack();
+        self.index_y = self.pull();
         self.update_zero_flag(self.index_y);
         self.update_negative_flag(self.index_y);
     }
 
-    fn logical_shift_right(&mut self) {
-        todo!();
+    fn reset_memory_bit(&mut self, bit: u8, mode: &AddressingMode, address: u16) {
+        let value = self.read_operand(mode, address) & !(1 << bit);
+        self.write_operand(mode, address, value);
     }
 
-    fn logical_inclusive_or(&mut self, operand: u8) {
-        self.accumulator |= self.memory[operand as usize];
-        self.update_zero_flag(self.accumulator);
-        self.update_negative_flag(self.accumulator);
+    fn set_memory_bit(&mut self, bit: u8, mode: &AddressingMode, address: u16) {
+        let value = self.read_operand(mode, address) | (1 << bit);
+        self.write_operand(mode, address, value);
     }
 
-    fn push_accumulator(&mut self) {
-        self.memory[self.stack_pointer as usize] = self.accumulator;
-        self.stack_pointer -= 1;
+    fn store_zero(&mut self, address: u16) {
+        self.write(address, 0);
     }
 
-    fn push_processor_status(&mut self) {
-        self.memory[self.stack_pointer as usize] = self.processor_status;
-        self.stack_pointer -= 1;
+    fn test_and_reset_bits(&mut self, mode: &AddressingMode, address: u16) {
+        let value = self.read_operand(mode, address);
+        self.update_zero_flag(self.accumulator & value);
+        self.write_operand(mode, address, value & !self.accumulator);
     }
 
-    fn pull_accumulator(&mut self) {
-        self.accumulator = self.memory[self.stack_pointer as usize];
-        self.update_zero_flag(self.accumulator);
-        self.update_negative_flag(self.accumulator);
-        self.stack_pointer += 1;
+    fn test_and_set_bits(&mut self, mode: &AddressingMode, address: u16) {
+        let value = self.read_operand(mode, address);
+        self.update_zero_flag(self.accumulator & value);
+        self.write_operand(mode, address, value | self.accumulator);
     }
 
-    fn pull_processor_status(&mut self) {
-        self.processor_status = self.memory[self.stack_pointer as usize];
-        self.stack_pointer += 1;
+    fn stop(&mut self) {
+        self.read(self.program_counter);
+        self.halt();
     }
 
-    fn rotate_left(&mut self) {
-        todo!();
+    fn wait_for_interrupt(&mut self) {
+        self.read(self.program_counter);
+        self.waiting = true;
     }
 
-    fn rotate_right(&mut self) {
-        todo!();
+    fn branch_if_carry_clear(&mut self, address: u16) {
+        self.branch(!self.processor_status.carry(), address);
     }
 
-    fn return_from_interrupt(&mut self) {
-        self.processor_status = self.memory[self.stack_pointer as usize];
-        self.stack_pointer += 1;
-        self.program_counter = self.memory[self.stack_pointer as usize] as u16;
-        self.stack_pointer += 1;
+    fn branch_if_carry_set(&mut self, address: u16) {
+        self.branch(self.processor_status.carry(), address);
     }
 
-    fn return_from_subroutine(&mut self) {
-        self.program_counter = self.memory[self.stack_pointer as usize] as u16 - 1;
-        self.stack_pointer += 1;
+    fn branch_if_equal(&mut self, address: u16) {
+        self.branch(self.processor_status.zero(), address);
     }
 
-    fn subtract_with_carry(&mut self) {
-        todo!();
+    fn branch_if_minus(&mut self, address: u16) {
+        self.branch(self.processor_status.negative(), address);
     }
 
-    fn store_accumulator(&mut self, operand: u8) {
-        self.memory[operand as usize] = self.accumulator;
+    fn branch_if_not_equal(&mut self, address: u16) {
+        self.branch(!self.processor_status.zero(), address);
     }
 
-    fn store_x_register(&mut self, operand: u8) {
-        self.memory[operand as usize] = self.index_x;
+    fn branch_if_positive(&mut self, address: u16) {
+        self.branch(!self.processor_status.negative(), address);
     }
 
-    fn store_y_register(&mut self, operand: u8) {
-        self.memory[operand as usize] = self.index_y;
+    fn branch_if_overflow_clear(&mut self, address: u16) {
+        self.branch(!self.processor_status.overflow(), address);
     }
 
-    fn decrement_x_register(&mut self) {
-        self.index_x -= 1;
-        self.update_zero_flag(self.index_x);
-        self.update_negative_flag(self.index_x);
+    fn branch_if_overflow_set(&mut self, address: u16) {
+        self.branch(self.processor_status.overflow(), address);
     }
 
-    fn decrement_y_register(&mut self) {
-        self.index_y -= 1;
-        self.update_zero_flag(self.index_y);
-        self.update_negative_flag(self.index_y);
+    // An NMI seen by the time the status is pushed takes over the vector
+    // fetch, hijacking a BRK or IRQ already in progress.
+    fn interrupt(&mut self, vector: u16, break_flag: bool) {
+        self.push_word(self.program_counter);
+        self.push(self.processor_status.to_pushed(break_flag));
+        let vector = if self.nmi_sampled {
+            self.nmi_taken = true;
+            NMI_VECTOR
+        } else {
+            vector
+        };
+        self.processor_status.set_interrupt_disable(true);
+        if self.variant == CpuVariant::Wdc65C02 {
+            self.processor_status.set_decimal(false);
+        }
+        self.program_counter = self.read_word(vector);
     }
 
-    fn increment_x_register(&mut self) {
-        self.index_x += 1;
-        self.update_zero_flag(self.index_x);
-        self.update_negative_flag(self.index_x);
+    fn push(&mut self, value: u8) {
+        self.write(0x0100 | self.stack_pointer as u16, value);
+        self.stack_pointer = self.stack_pointer.wrapping_sub(1);
     }
 
-    fn increment_y_register(&mut self) {
-        self.index_y += 1;
-        self.update_zero_flag(self.index_y);
-        self.update_negative_flag(self.index_y);
+    fn push_word(&mut self, value: u16) {
+        let [low, high] = value.to_le_bytes();
+        self.push(high);
+        self.push(low);
     }
 
-    fn force_interrupt(&mut self) {
-        self.processor_status |= 0b0011_0000;
+    fn read_stack(&mut self) {
+        self.read(0x0100 | self.stack_pointer as u16);
     }
 
-    fn clear_carry_flag(&mut self) {
-        self.processor_status &= 0b1111_1110;
+    fn pull(&mut self) -> u8 {
+        self.stack_pointer = self.stack_pointer.wrapping_add(1);
+        self.read(0x0100 | self.stack_pointer as u16)
     }
 
-    fn clear_decimal_mode(&mut self) {
-        self.processor_status &= 0b1111_0111;
+    fn pull_word(&mut self) -> u16 {
+        let low = self.pull();
+        let high = self.pull();
+        u16::from_le_bytes([low, high])
     }
 
-    fn clear_interrupt_disable(&mut self) {
-        self.processor_status &= 0b1111_1011;
+    fn add(&mut self, value: u8) {
+        if self.decimal_mode_enabled() {
+            self.add_decimal(value);
+        } else {
+            self.add_binary(value);
+        }
     }
 
-    fn clear_overflow_flag(&mut self) {
-        self.processor_status &= 0b1011_1111;
+    fn subtract(&mut self, value: u8) {
+        if self.decimal_mode_enabled() {
+            self.subtract_decimal(value);
+        } else {
+            self.add_binary(!value);
+        }
     }
 
-    fn no_operation(&mut self) {}
+    fn decimal_mode_enabled(&self) -> bool {
+        self.processor_status.decimal() && self.variant != CpuVariant::Ricoh2A03
+    }
 
-    fn set_carry_flag(&mut self) {
-        self.processor_status |= 0b0000_0001;
+    fn add_binary(&mut self, value: u8) {
+        let carry = self.processor_status.carry() as u16;
+        let sum = self.accumulator as u16 + value as u16 + carry;
+        let result = sum as u8;
+        self.processor_status.set_carry(sum > 0xFF);
+        self.processor_status
+            .set_overflow((self.accumulator ^ result) & (value ^ result) & 0b1000_0000 != 0);
+        self.accumulator = result;
+        self.update_zero_flag(result);
+        self.update_negative_flag(result);
     }
 
-    fn set_decimal_flag(&mut self) {
-        self.processor_status |= 0b0000_1000;
+    // NMOS decimal mode: N and V reflect the sum before the high nibble is
+    // adjusted, and Z reflects the plain binary sum. The 65C02 spends an
+    // extra cycle to set N and Z from the decimal result.
+    fn add_decimal(&mut self, value: u8) {
+        let carry = self.processor_status.carry() as i16;
+        let binary = self
+            .accumulator
+            .wrapping_add(value)
+            .wrapping_add(carry as u8);
+
+        let mut low = (self.accumulator & 0x0F) as i16 + (value & 0x0F) as i16 + carry;
+        if low >= 0x0A {
+            low = ((low + 0x06) & 0x0F) + 0x10;
+        }
+
+        let mut sum = (self.accumulator & 0xF0) as i16 + (value & 0xF0) as i16 + low;
+        let signed = (self.accumulator & 0xF0) as i8 as i16 + (value & 0xF0) as i8 as i16 + low;
+        self.processor_status
+            .set_overflow(!(-128..=127).contains(&signed));
+        self.update_negative_flag(sum as u8);
+        self.update_zero_flag(binary);
+
+        if sum >= 0xA0 {
+            sum += 0x60;
+        }
+        self.processor_status.set_carry(sum >= 0x100);
+        self.accumulator = sum as u8;
+
+        if self.variant == CpuVariant::Wdc65C02 {
+            self.update_zero_flag(self.accumulator);
+            self.update_negative_flag(self.accumulator);
+            self.read(self.program_counter);
+        }
     }
 
-    fn set_interrupt_disable(&mut self) {
-        self.processor_status |= 0b0000_0100;
+    // NMOS decimal mode: all flags match binary subtraction. The 65C02
+    // adjusts the whole result at once and sets N and Z from it.
+    fn subtract_decimal(&mut self, value: u8) {
+        let carry = self.processor_status.carry() as i16;
+        let accumulator = self.accumulator as i16;
+        let operand = value as i16;
+        let low = (accumulator & 0x0F) - (operand & 0x0F) + carry - 1;
+
+        let result = if self.variant == CpuVariant::Wdc65C02 {
+            let mut result = accumulator - operand + carry - 1;
+            if result < 0 {
+                result -= 0x60;
+            }
+            if low < 0 {
+                result -= 0x06;
+            }
+            result
+        } else {
+            let low = if low < 0 {
+                ((low - 0x06) & 0x0F) - 0x10
+            } else {
+                low
+            };
+            let mut result = (accumulator & 0xF0) - (operand & 0xF0) + low;
+            if result < 0 {
+                result -= 0x60;
+            }
+            result
+        };
+
+        self.add_binary(!value);
+        self.accumulator = result as u8;
+
+        if self.variant == CpuVariant::Wdc65C02 {
+            self.update_zero_flag(self.accumulator);
+            self.update_negative_flag(self.accumulator);
+            self.read(self.program_counter);
+        }
     }
 
-    fn transfer_accumulator_to_x(&mut self) {
-        self.index_x = self.accumulator;
-        self.update_zero_flag(self.index_x);
-        self.update_negative_flag(self.index_x);
+    // A taken branch polls before its operand fetch and again only before a
+    // page fix-up, so without a page cross an interrupt is held off.
+    fn branch(&mut self, condition: bool, address: u16) {
+        if condition {
+            let poll = self.poll;
+            self.read(self.program_counter);
+            if Self::crosses_page(self.program_counter, address) {
+                self.read((self.program_counter & 0xFF00) | (address & 0x00FF));
+            } else {
+                self.poll = poll;
+            }
+            self.program_counter = address;
+        }
     }
 
-    fn transfer_accumulator_to_y(&mut self) {
-        self.index_y = self.accumulator;
-        self.update_zero_flag(self.index_y);
-        self.update_negative_flag(self.index_y);
+    fn compare_register(&mut self, register: u8, value: u8) {
+        let result = register.wrapping_sub(value);
+        self.processor_status.set_carry(register >= value);
+        self.update_zero_flag(result);
+        self.update_negative_flag(result);
     }
 
-    fn transfer_stack_pointer_to_x(&mut self) {
-        self.index_x = self.stack_pointer;
-        self.update_zero_flag(self.index_x);
-        self.update_negative_flag(self.index_x);
+    fn update_zero_flag(&mut self, operand: u8) {
+        self.processor_status.set_zero(operand == 0);
     }
 
-    fn transfer_x_to_accumulator(&mut self) {
-        self.accumulator = self.index_x;
-        self.update_zero_flag(self.accumulator);
-        self.update_negative_flag(self.accumulator);
+    fn update_negative_flag(&mut self, operand: u8) {
+        self.processor_status
+            .set_negative(operand & 0b1000_0000 != 0);
     }
+}
 
-    fn transfer_x_to_stack_pointer(&mut self) {
-        self.stack_pointer = self.index_x;
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const START: u16 = 0x0400;
+
+    fn cpu_with(variant: CpuVariant, program: &[u8], memory: &[(u16, u8)]) -> Cpu<Ram> {
+        let mut ram = Ram::new();
+        ram.load(START, program);
+        for &(address, value) in memory {
+            ram.write(address, value);
+        }
+        let mut cpu = Cpu::with_variant(variant, ram);
+        cpu.set_program_counter(START);
+        cpu
     }
 
-    fn transfer_y_to_accumulator(&mut self) {
-        self.accumulator = self.index_y;
-        self.update_zero_flag(self.accumulator);
-        self.update_negative_flag(self.accumulator);
+    #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+    enum Access {
+        Read(u16),
+        Write(u16, u8),
+    }
+
+    struct CountingBus {
+        ram: Ram,
+        accesses: Vec<Access>,
     }
 
-    fn branch_if_carry_clear(&mut self, operand: u8) {
-        if self.processor_status & 0b0000_0001 == 0 {
-            self.program_counter += operand as u16;
+    impl Bus for CountingBus {
+        fn read(&mut self, address: u16) -> u8 {
+            self.accesses.push(Access::Read(address));
+            self.ram.read(address)
+        }
+
+        fn write(&mut self, address: u16, value: u8) {
+            self.accesses.push(Access::Write(address, value));
+            self.ram.write(address, value);
         }
     }
 
-    fn branch_if_carry_set(&mut self, operand: u8) {
-        if self.processor_status & 0b0000_0001 != 0 {
-            self.program_counter += operand as u16;
+    fn counting_cpu(program: &[u8], memory: &[(u16, u8)]) -> Cpu<CountingBus> {
+        let mut ram = Ram::new();
+        ram.load(START, program);
+        for &(address, value) in memory {
+            ram.write(address, value);
         }
+        let mut cpu = Cpu::with_bus(CountingBus {
+            ram,
+            accesses: Vec::new(),
+        });
+        cpu.set_state(CpuState {
+            index_x: 0x10,
+            status: StatusFlags::from_bits(0x20),
+            stack_pointer: 0xFD,
+            program_counter: START,
+            ..CpuState::default()
+        });
+        cpu
+    }
+
+    // Ticks through one instruction, checking that every tick makes exactly
+    // one new bus access, then checks that step() makes the same accesses.
+    fn assert_ticks_match_step(
+        setup: impl Fn() -> Cpu<CountingBus>,
+        expected_cycles: u8,
+    ) -> Vec<Access> {
+        let mut ticked = setup();
+        let before = ticked.bus().accesses.len();
+        let mut ticks = 0;
+        let info = loop {
+            let result = ticked.tick().unwrap();
+            ticks += 1;
+            assert_eq!(ticked.bus().accesses.len(), before + ticks);
+            assert_eq!(ticked.cycles(), (before + ticks) as u64);
+            if let Some(info) = result {
+                break info;
+            }
+        };
+        assert_eq!(ticks, expected_cycles as usize);
+        assert_eq!(info.cycles, expected_cycles);
+
+        let mut stepped = setup();
+        assert_eq!(stepped.step().unwrap(), info);
+        assert_eq!(stepped.state(), ticked.state());
+        assert_eq!(stepped.cycles(), ticked.cycles());
+        assert_eq!(stepped.bus().accesses, ticked.bus().accesses);
+        ticked.bus().accesses[before..].to_vec()
+    }
+
+    #[test]
+    fn setting_registers_between_ticks_abandons_the_instruction() {
+        let mut cpu = cpu_with(CpuVariant::Nmos6502, &[0xEA], &[(0x0600, 0xEA)]);
+        assert_eq!(cpu.tick().unwrap(), None);
+        cpu.set_state(CpuState {
+            accumulator: 7,
+            program_counter: 0x0600,
+            ..cpu.state()
+        });
+        assert_eq!(cpu.tick().unwrap(), None);
+        assert_eq!(cpu.tick().unwrap().map(|info| info.pc), Some(0x0600));
+        assert_eq!((cpu.accumulator(), cpu.program_counter()), (7, 0x0601));
+
+        assert_eq!(cpu.tick().unwrap(), None);
+        cpu.set_program_counter(START);
+        assert_eq!(cpu.tick().unwrap(), None);
+        assert_eq!(cpu.tick().unwrap().map(|info| info.pc), Some(START));
+    }
+
+    #[test]
+    fn ticks_while_waiting_make_no_access() {
+        let mut ram = Ram::new();
+        ram.load(START, &[0xCB, 0xEA]);
+        let bus = CountingBus {
+            ram,
+            accesses: Vec::new(),
+        };
+        let mut cpu = Cpu::with_variant(CpuVariant::Wdc65C02, bus);
+        cpu.set_program_counter(START);
+        cpu.step().unwrap();
+
+        let accesses = cpu.bus().accesses.len();
+        let cycles = cpu.cycles();
+        assert_eq!(cpu.tick().unwrap().map(|info| info.cycles), Some(1));
+        assert_eq!(cpu.bus().accesses.len(), accesses);
+        assert_eq!(cpu.cycles(), cycles + 1);
     }
 
-    fn branch_if_equal(&mut self, operand: u8) {
-        if self.processor_status & 0b0000_0010 != 0 {
-            self.program_counter += operand as u16;
+    #[test]
+    fn stalled_ticks_make_no_access() {
+        let mut cpu = counting_cpu(&[0xEA], &[]);
+        cpu.stall(3);
+        for _ in 0..3 {
+            assert_eq!(cpu.tick().unwrap(), None);
         }
+        assert!(cpu.bus().accesses.is_empty());
+        assert_eq!(cpu.cycles(), 3);
+
+        assert_eq!(cpu.tick().unwrap(), None);
+        assert_eq!(cpu.bus().accesses, [Access::Read(START)]);
+    }
+
+    #[test]
+    fn stalls_wait_for_the_current_instruction() {
+        let mut cpu = counting_cpu(&[0xEA, 0xEA], &[]);
+        assert_eq!(cpu.tick().unwrap(), None);
+        cpu.stall(2);
+        let info = cpu.tick().unwrap().unwrap();
+        assert_eq!((info.pc, info.cycles), (START, 2));
+
+        let info = cpu.step().unwrap();
+        assert_eq!((info.pc, info.cycles), (START + 1, 2));
+        assert_eq!(cpu.cycles(), 6);
+        assert_eq!(cpu.bus().accesses.len(), 4);
+    }
+
+    #[test]
+    fn irq_raised_during_a_stall_follows_the_next_instruction() {
+        let mut cpu = interrupt_cpu(&[0xEA, 0xEA], 0x20, 0xFD);
+        cpu.stall(4);
+        tick_without_finishing(&mut cpu, 2);
+        cpu.set_irq(true);
+        tick_without_finishing(&mut cpu, 2);
+
+        assert_eq!(cpu.step().unwrap().pc, START);
+        let info = cpu.step().unwrap();
+        assert_eq!(info.interrupt, Some(Interrupt::Irq));
+        assert_eq!(cpu.program_counter(), IRQ_HANDLER);
+    }
+
+    #[test]
+    fn inc_absolute_x_ticks_one_access_at_a_time() {
+        let accesses =
+            assert_ticks_match_step(|| counting_cpu(&[0xFE, 0x00, 0x02], &[(0x0210, 0x41)]), 7);
+        assert_eq!(
+            accesses,
+            [
+                Access::Read(0x0400),
+                Access::Read(0x0401),
+                Access::Read(0x0402),
+                Access::Read(0x0210),
+                Access::Read(0x0210),
+                Access::Write(0x0210, 0x41),
+                Access::Write(0x0210, 0x42),
+            ]
+        );
+    }
+
+    #[test]
+    fn lda_absolute_x_across_a_page_ticks_one_access_at_a_time() {
+        let accesses =
+            assert_ticks_match_step(|| counting_cpu(&[0xBD, 0xF8, 0x02], &[(0x0308, 0x99)]), 5);
+        assert_eq!(
+            accesses,
+            [
+                Access::Read(0x0400),
+                Access::Read(0x0401),
+                Access::Read(0x0402),
+                Access::Read(0x0208),
+                Access::Read(0x0308),
+            ]
+        );
+    }
+
+    #[test]
+    fn jsr_ticks_one_access_at_a_time() {
+        let accesses = assert_ticks_match_step(|| counting_cpu(&[0x20, 0x34, 0x12], &[]), 6);
+        assert_eq!(
+            accesses,
+            [
+                Access::Read(0x0400),
+                Access::Read(0x0401),
+                Access::Read(0x01FD),
+                Access::Write(0x01FD, 0x04),
+                Access::Write(0x01FC, 0x02),
+                Access::Read(0x0402),
+            ]
+        );
+    }
+
+    #[test]
+    fn brk_ticks_one_access_at_a_time() {
+        let vector = [(0xFFFE, 0x00), (0xFFFF, 0x08)];
+        let accesses = assert_ticks_match_step(|| counting_cpu(&[0x00, 0xEA], &vector), 7);
+        assert_eq!(
+            accesses,
+            [
+                Access::Read(0x0400),
+                Access::Read(0x0401),
+                Access::Write(0x01FD, 0x04),
+                Access::Write(0x01FC, 0x02),
+                Access::Write(0x01FB, 0x30),
+                Access::Read(0xFFFE),
+                Access::Read(0xFFFF),
+            ]
+        );
+    }
+
+    #[test]
+    fn interrupt_sequence_ticks_one_access_at_a_time() {
+        let setup = || {
+            let mut cpu = counting_cpu(&[0xEA, 0xEA], &[(0xFFFE, 0x00), (0xFFFF, 0x08)]);
+            cpu.set_irq(true);
+            cpu.step().unwrap();
+            cpu
+        };
+        let accesses = assert_ticks_match_step(setup, 7);
+        assert_eq!(
+            accesses,
+            [
+                Access::Read(0x0401),
+                Access::Read(0x0401),
+                Access::Write(0x01FD, 0x04),
+                Access::Write(0x01FC, 0x01),
+                Access::Write(0x01FB, 0x20),
+                Access::Read(0xFFFE),
+                Access::Read(0xFFFF),
+            ]
+        );
     }
 
-    fn branch_if_minus(&mut self, operand: u8) {
-        if self.processor_status & 0b1000_0000 != 0 {
-            self.program_counter += operand as u16;
+    const IRQ_HANDLER: u16 = 0x0800;
+    const NMI_HANDLER: u16 = 0x0900;
+
+    // A CPU with both vectors set up and NOPs at the handlers.
+    fn interrupt_cpu(program: &[u8], status: u8, stack_pointer: u8) -> Cpu<Ram> {
+        let memory = [
+            (0xFFFA, 0x00),
+            (0xFFFB, 0x09),
+            (0xFFFE, 0x00),
+            (0xFFFF, 0x08),
+            (IRQ_HANDLER, 0xEA),
+            (NMI_HANDLER, 0xEA),
+        ];
+        let mut cpu = cpu_with(CpuVariant::Nmos6502, program, &memory);
+        cpu.set_state(CpuState {
+            status: StatusFlags::from_bits(status),
+            stack_pointer,
+            program_counter: START,
+            ..CpuState::default()
+        });
+        cpu
+    }
+
+    fn tick_without_finishing(cpu: &mut Cpu<Ram>, ticks: usize) {
+        for _ in 0..ticks {
+            assert_eq!(cpu.tick().unwrap(), None);
         }
     }
 
-    fn branch_if_not_equal(&mut self, operand: u8) {
-        if self.processor_status & 0b0000_0010 == 0 {
-            self.program_counter += operand as u16;
+    #[test]
+    fn cli_delays_an_irq_by_one_instruction() {
+        let mut cpu = interrupt_cpu(&[0x58, 0xEA, 0xEA], 0x24, 0xFD);
+        cpu.set_irq(true);
+        assert_eq!(cpu.step().unwrap().interrupt, None);
+        let info = cpu.step().unwrap();
+        assert_eq!((info.pc, info.interrupt), (START + 1, None));
+        assert_eq!(cpu.step().unwrap().interrupt, Some(Interrupt::Irq));
+        assert_eq!(cpu.program_counter(), IRQ_HANDLER);
+    }
+
+    #[test]
+    fn sei_lets_a_pending_irq_through_once() {
+        let mut cpu = interrupt_cpu(&[0x78, 0xEA], 0x20, 0xFD);
+        cpu.set_irq(true);
+        assert_eq!(cpu.step().unwrap().interrupt, None);
+        assert_eq!(cpu.step().unwrap().interrupt, Some(Interrupt::Irq));
+        // SEI has completed, so the pushed status already has the flag set.
+        assert_eq!(cpu.bus_mut().read(0x01FB), 0x24);
+    }
+
+    #[test]
+    fn plp_delays_an_irq_by_one_instruction() {
+        let mut cpu = interrupt_cpu(&[0x28, 0xEA, 0xEA], 0x24, 0xFC);
+        cpu.bus_mut().write(0x01FD, 0x20);
+        cpu.set_irq(true);
+        assert_eq!(cpu.step().unwrap().interrupt, None);
+        let info = cpu.step().unwrap();
+        assert_eq!((info.pc, info.interrupt), (START + 1, None));
+        assert_eq!(cpu.step().unwrap().interrupt, Some(Interrupt::Irq));
+    }
+
+    #[test]
+    fn rti_restores_the_interrupt_flag_at_once() {
+        let mut cpu = interrupt_cpu(&[0x40], 0x24, 0xFA);
+        cpu.bus_mut().write(0x01FB, 0x20);
+        cpu.bus_mut().write(0x01FC, 0x00);
+        cpu.bus_mut().write(0x01FD, 0x05);
+        cpu.set_irq(true);
+        assert_eq!(cpu.step().unwrap().interrupt, None);
+        let info = cpu.step().unwrap();
+        assert_eq!((info.pc, info.interrupt), (0x0500, Some(Interrupt::Irq)));
+    }
+
+    #[test]
+    fn taken_branch_without_page_cross_holds_off_an_irq() {
+        let mut cpu = interrupt_cpu(&[0xF0, 0x00, 0xEA, 0xEA], 0x22, 0xFD);
+        tick_without_finishing(&mut cpu, 2);
+        cpu.set_irq(true);
+        assert_eq!(cpu.tick().unwrap().map(|info| info.cycles), Some(3));
+        let info = cpu.step().unwrap();
+        assert_eq!((info.pc, info.interrupt), (START + 2, None));
+        assert_eq!(cpu.step().unwrap().interrupt, Some(Interrupt::Irq));
+    }
+
+    #[test]
+    fn taken_branch_across_a_page_does_not_hold_off_an_irq() {
+        let mut cpu = interrupt_cpu(&[0xF0, 0xF0], 0x22, 0xFD);
+        cpu.bus_mut().write(0x03F2, 0xEA);
+        tick_without_finishing(&mut cpu, 3);
+        cpu.set_irq(true);
+        assert_eq!(cpu.tick().unwrap().map(|info| info.cycles), Some(4));
+        let info = cpu.step().unwrap();
+        assert_eq!((info.pc, info.interrupt), (0x03F2, Some(Interrupt::Irq)));
+    }
+
+    #[test]
+    fn nmi_hijacks_brk() {
+        let mut cpu = interrupt_cpu(&[0x00, 0xEA], 0x20, 0xFD);
+        tick_without_finishing(&mut cpu, 4);
+        cpu.nmi();
+        tick_without_finishing(&mut cpu, 2);
+        let info = cpu.tick().unwrap().unwrap();
+        assert_eq!((info.opcode, info.interrupt), (0x00, None));
+        assert_eq!(cpu.program_counter(), NMI_HANDLER);
+        // The pushed status still marks the entry as a BRK.
+        assert_eq!(cpu.bus_mut().read(0x01FB), 0x30);
+        // The hijack consumed the NMI.
+        assert_eq!(cpu.step().unwrap().interrupt, None);
+    }
+
+    #[test]
+    fn nmi_hijacks_an_irq() {
+        let mut cpu = interrupt_cpu(&[0xEA, 0xEA], 0x20, 0xFD);
+        cpu.set_irq(true);
+        cpu.step().unwrap();
+        tick_without_finishing(&mut cpu, 4);
+        cpu.nmi();
+        tick_without_finishing(&mut cpu, 2);
+        let info = cpu.tick().unwrap().unwrap();
+        assert_eq!(info.interrupt, Some(Interrupt::Nmi));
+        assert_eq!(cpu.program_counter(), NMI_HANDLER);
+        assert_eq!(cpu.bus_mut().read(0x01FB), 0x20);
+        cpu.set_irq(false);
+        assert_eq!(cpu.step().unwrap().interrupt, None);
+    }
+
+    #[test]
+    fn cpu_and_machine_are_send() {
+        fn assert_send<T: Send>() {}
+        assert_send::<Cpu<Ram>>();
+        assert_send::<crate::machine::Machine<Ram>>();
+    }
+
+    #[test]
+    fn instruction_limit_of_zero_stops_immediately() {
+        let mut cpu = cpu_with(CpuVariant::Nmos6502, &[0xEA], &[]);
+        cpu.set_stop_conditions(StopConditions {
+            instruction_limit: Some(0),
+            ..StopConditions::default()
+        });
+        assert_eq!(cpu.run(), StopReason::InstructionLimit);
+        assert_eq!(cpu.cycles(), 0);
+    }
+
+    #[test]
+    fn run_for_cycles_saturates_the_target() {
+        let mut cpu = cpu_with(CpuVariant::Nmos6502, &[0xEA, 0xEA, 0xEA], &[]);
+        cpu.step().unwrap();
+        cpu.set_stop_conditions(StopConditions {
+            instruction_limit: Some(2),
+            ..StopConditions::default()
+        });
+        assert_eq!(cpu.run_for_cycles(u64::MAX), StopReason::InstructionLimit);
+        assert_eq!(cpu.cycles(), 6);
+    }
+
+    const VARIANTS: [CpuVariant; 3] = [
+        CpuVariant::Nmos6502,
+        CpuVariant::Ricoh2A03,
+        CpuVariant::Wdc65C02,
+    ];
+
+    #[test]
+    fn undocumented_opcodes_are_illegal_only_when_disabled() {
+        for &variant in &VARIANTS {
+            for byte in 0..=0xFFu8 {
+                let documented = variant.opcodes()[byte as usize].documented;
+                for &enabled in &[true, false] {
+                    let mut cpu = cpu_with(variant, &[byte, 0x00, 0x00], &[]);
+                    cpu.set_undocumented_opcodes(enabled);
+                    let result = cpu.step();
+                    if documented || enabled {
+                        assert!(
+                            !matches!(result, Err(CpuError::IllegalOpcode { .. })),
+                            "{:?} ${:02X}",
+                            variant,
+                            byte
+                        );
+                    } else {
+                        assert_eq!(
+                            result,
+                            Err(CpuError::IllegalOpcode {
+                                pc: START,
+                                opcode: byte
+                            }),
+                            "{:?} ${:02X}",
+                            variant,
+                            byte
+                        );
+                        assert_eq!(cpu.program_counter(), START);
+                    }
+                }
+            }
         }
     }
 
-    fn branch_if_positive(&mut self, operand: u8) {
-        if self.processor_status & 0b1000_0000 == 0 {
-            self.program_counter += operand as u16;
+    #[test]
+    fn cmos_nop_is_documented() {
+        let mut cpu = cpu_with(CpuVariant::Wdc65C02, &[0xEA], &[]);
+        cpu.set_undocumented_opcodes(false);
+        assert_eq!(cpu.step().unwrap().cycles, 2);
+    }
+
+    #[test]
+    fn power_on_runs_the_reset_sequence() {
+        let memory = [(0xFFFC, 0x34), (0xFFFD, 0x12)];
+        for &variant in &VARIANTS {
+            let mut cpu = cpu_with(variant, &[], &memory);
+            cpu.power_on();
+            assert_eq!(cpu.program_counter(), 0x1234, "{:?}", variant);
+            assert_eq!(cpu.stack_pointer(), 0xFD, "{:?}", variant);
+            assert_eq!(cpu.status().bits(), 0x24, "{:?}", variant);
+            assert_eq!(cpu.cycles(), 7, "{:?}", variant);
         }
     }
 
-    fn branch_if_overflow_clear(&mut self, operand: u8) {
-        if self.processor_status & 0b0100_0000 == 0 {
-            self.program_counter += operand as u16;
+    #[test]
+    fn reset_keeps_registers_and_drops_the_stack_pointer() {
+        let memory = [(0xFFFC, 0x00), (0xFFFD, 0x06)];
+        for &variant in &VARIANTS {
+            let mut cpu = cpu_with(variant, &[0xF8], &memory); // SED
+            cpu.set_state(CpuState {
+                program_counter: START,
+                accumulator: 0x11,
+                index_x: 0x22,
+                index_y: 0x33,
+                stack_pointer: 0xF0,
+                ..CpuState::default()
+            });
+            cpu.step().unwrap();
+            cpu.reset();
+
+            let registers = (cpu.accumulator(), cpu.index_x(), cpu.index_y());
+            assert_eq!(registers, (0x11, 0x22, 0x33), "{:?}", variant);
+            assert_eq!(cpu.stack_pointer(), 0xED, "{:?}", variant);
+            assert_eq!(cpu.program_counter(), 0x0600, "{:?}", variant);
+            assert!(cpu.status().interrupt_disable(), "{:?}", variant);
+            let cleared = variant == CpuVariant::Wdc65C02;
+            assert_eq!(cpu.status().decimal(), !cleared, "{:?}", variant);
         }
     }
 
-    fn branch_if_overflow_set(&mut self, operand: u8) {
-        if self.processor_status & 0b0100_0000 != 0 {
-            self.program_counter += operand as u16;
+    #[test]
+    fn jam_and_stp_halt_the_cpu() {
+        let cases = [
+            (CpuVariant::Nmos6502, 0x02),
+            (CpuVariant::Ricoh2A03, 0x12),
+            (CpuVariant::Wdc65C02, 0xDB),
+        ];
+        for &(variant, opcode) in &cases {
+            let mut cpu = cpu_with(variant, &[opcode, 0xEA], &[]);
+            let halted = Err(CpuError::Halted { pc: START, opcode });
+            assert_eq!(cpu.step(), halted);
+            // The CPU stays halted until it is reset.
+            assert_eq!(cpu.step(), halted);
         }
     }
 
-    fn update_zero_flag(&mut self, operand: u8) {
-        if operand == 0 {
-            self.processor_status |= 0b0000_0010;
-        } else {
-            self.processor_status &= 0b1111_1101;
+    struct FaultingBus {
+        ram: Ram,
+        fault: Option<u16>,
+    }
+
+    impl Bus for FaultingBus {
+        fn read(&mut self, address: u16) -> u8 {
+            self.ram.read(address)
+        }
+
+        // Nothing is mapped at $4000-$7FFF.
+        fn write(&mut self, address: u16, value: u8) {
+            match address {
+                0x4000..=0x7FFF => self.fault = self.fault.or(Some(address)),
+                _ => self.ram.write(address, value),
+            }
+        }
+
+        fn take_fault(&mut self) -> Option<u16> {
+            self.fault.take()
         }
     }
 
-    fn update_negative_flag(&mut self, operand: u8) {
-        if operand & 0b1000_0000 != 0 {
-            self.processor_status |= 0b1000_0000;
-        } else {
-            self.processor_status &= 0b0111_1111;
+    #[test]
+    fn bus_faults_are_reported_after_the_instruction() {
+        let mut ram = Ram::new();
+        ram.load(START, &[0x8D, 0x00, 0x40, 0xEA]);
+        let mut cpu = Cpu::with_bus(FaultingBus { ram, fault: None });
+        cpu.set_program_counter(START);
+        assert_eq!(
+            cpu.step(),
+            Err(CpuError::BusFault {
+                pc: START,
+                opcode: 0x8D,
+                address: 0x4000
+            })
+        );
+        assert_eq!(cpu.program_counter(), START + 3);
+        assert_eq!(cpu.step().unwrap().pc, START + 3);
+    }
+
+    // Runs ADC or SBC immediate in decimal mode and returns A, the N, V, Z
+    // and C flags as letters, upper case when set, and the cycle count.
+    fn decimal_op(
+        variant: CpuVariant,
+        opcode: u8,
+        accumulator: u8,
+        operand: u8,
+        carry: bool,
+    ) -> (u8, String, u8) {
+        let mut cpu = cpu_with(variant, &[opcode, operand], &[]);
+        cpu.set_state(CpuState {
+            accumulator,
+            status: StatusFlags::from_bits(0x28 | carry as u8),
+            program_counter: START,
+            ..CpuState::default()
+        });
+        let cycles = cpu.step().unwrap().cycles;
+        let status = cpu.status();
+        let flags = [
+            ('n', status.negative()),
+            ('v', status.overflow()),
+            ('z', status.zero()),
+            ('c', status.carry()),
+        ]
+        .iter()
+        .map(|&(letter, set)| {
+            if set {
+                letter.to_ascii_uppercase()
+            } else {
+                letter
+            }
+        })
+        .collect();
+        (cpu.accumulator(), flags, cycles)
+    }
+
+    const ADC: u8 = 0x69;
+    const SBC: u8 = 0xE9;
+
+    #[test]
+    fn nmos_decimal_flags() {
+        let cases = [
+            // N and V come from the sum before the high nibble is adjusted,
+            // Z from the binary sum.
+            (ADC, 0x99, 0x01, false, 0x00, "NvzC"),
+            (ADC, 0x79, 0x00, true, 0x80, "NVzc"),
+            (ADC, 0x50, 0x50, false, 0x00, "NVzC"),
+            (ADC, 0x12, 0x34, false, 0x46, "nvzc"),
+            // SBC sets every flag as binary subtraction would.
+            (SBC, 0x00, 0x01, true, 0x99, "Nvzc"),
+            (SBC, 0x46, 0x12, true, 0x34, "nvzC"),
+            (SBC, 0x10, 0x10, true, 0x00, "nvZC"),
+        ];
+        for &(opcode, a, operand, carry, result, flags) in &cases {
+            assert_eq!(
+                decimal_op(CpuVariant::Nmos6502, opcode, a, operand, carry),
+                (result, flags.to_string(), 2),
+                "${:02X} on {:02X} {:02X}",
+                opcode,
+                a,
+                operand
+            );
+        }
+    }
+
+    #[test]
+    fn cmos_decimal_flags_come_from_the_result() {
+        let cases = [
+            (ADC, 0x99, 0x01, false, 0x00, "nvZC"),
+            (ADC, 0x79, 0x00, true, 0x80, "NVzc"),
+            (ADC, 0x50, 0x50, false, 0x00, "nVZC"),
+            (SBC, 0x00, 0x01, true, 0x99, "Nvzc"),
+            (SBC, 0x10, 0x10, true, 0x00, "nvZC"),
+        ];
+        for &(opcode, a, operand, carry, result, flags) in &cases {
+            // The extra cycle is spent fixing up N and Z.
+            assert_eq!(
+                decimal_op(CpuVariant::Wdc65C02, opcode, a, operand, carry),
+                (result, flags.to_string(), 3),
+                "${:02X} on {:02X} {:02X}",
+                opcode,
+                a,
+                operand
+            );
+        }
+    }
+
+    #[test]
+    fn ricoh_2a03_ignores_decimal_mode() {
+        let cases = [
+            (ADC, 0x09, 0x01, false, 0x0A, "nvzc"),
+            (SBC, 0x10, 0x01, true, 0x0F, "nvzC"),
+        ];
+        for &(opcode, a, operand, carry, result, flags) in &cases {
+            assert_eq!(
+                decimal_op(CpuVariant::Ricoh2A03, opcode, a, operand, carry),
+                (result, flags.to_string(), 2)
+            );
+        }
+    }
+
+    fn run_undocumented(
+        variant: CpuVariant,
+        program: &[u8],
+        memory: &[(u16, u8)],
+        state: CpuState,
+    ) -> Cpu<Ram> {
+        let mut cpu = cpu_with(variant, program, memory);
+        cpu.set_state(CpuState {
+            program_counter: START,
+            ..state
+        });
+        cpu.step().unwrap();
+        cpu
+    }
+
+    fn flags(cpu: &Cpu<Ram>) -> (bool, bool, bool, bool) {
+        let status = cpu.status();
+        (
+            status.negative(),
+            status.overflow(),
+            status.zero(),
+            status.carry(),
+        )
+    }
+
+    #[test]
+    fn arr_binary_sets_c_and_v_from_bits_six_and_five() {
+        let state = CpuState {
+            accumulator: 0x80,
+            ..CpuState::default()
+        };
+        let cpu = run_undocumented(CpuVariant::Nmos6502, &[0x6B, 0xFF], &[], state);
+        assert_eq!(cpu.accumulator(), 0x40);
+        assert_eq!(flags(&cpu), (false, true, false, true));
+    }
+
+    #[test]
+    fn arr_decimal_adjusts_each_nibble_on_nmos_only() {
+        let state = CpuState {
+            accumulator: 0x66,
+            status: StatusFlags::from_bits(0x28),
+            ..CpuState::default()
+        };
+        let cpu = run_undocumented(CpuVariant::Nmos6502, &[0x6B, 0xFF], &[], state);
+        assert_eq!(cpu.accumulator(), 0x99);
+        assert_eq!(flags(&cpu), (false, true, false, true));
+
+        // The 2A03 has no decimal mode, so it takes the binary path.
+        let cpu = run_undocumented(CpuVariant::Ricoh2A03, &[0x6B, 0xFF], &[], state);
+        assert_eq!(cpu.accumulator(), 0x33);
+        assert_eq!(flags(&cpu), (false, true, false, false));
+    }
+
+    #[test]
+    fn sbx_subtracts_from_a_and_x_without_borrow_or_decimal() {
+        let state = CpuState {
+            accumulator: 0xF0,
+            index_x: 0x3C,
+            status: StatusFlags::from_bits(0x28),
+            ..CpuState::default()
+        };
+        let cpu = run_undocumented(CpuVariant::Nmos6502, &[0xCB, 0x10], &[], state);
+        assert_eq!((cpu.index_x(), cpu.accumulator()), (0x20, 0xF0));
+        assert_eq!(flags(&cpu), (false, false, false, true));
+
+        let cpu = run_undocumented(CpuVariant::Nmos6502, &[0xCB, 0x40], &[], state);
+        assert_eq!(cpu.index_x(), 0xF0);
+        assert_eq!(flags(&cpu), (true, false, false, false));
+    }
+
+    // SHA, SHX, SHY and TAS store the value ANDed with the base high byte
+    // plus one; when indexing crosses a page, that value also replaces the
+    // high byte of the address.
+    #[test]
+    fn high_byte_stores_without_page_cross() {
+        let cases = [
+            (0x9E, 0x12, 0xFF, 0x10), // SHX $1200,Y
+            (0x9C, 0x12, 0x10, 0xFF), // SHY $1200,X
+        ];
+        for &(opcode, high, x, y) in &cases {
+            let state = CpuState {
+                index_x: x,
+                index_y: y,
+                ..CpuState::default()
+            };
+            let mut cpu = run_undocumented(CpuVariant::Nmos6502, &[opcode, 0x00, high], &[], state);
+            assert_eq!(cpu.bus_mut().read(0x1210), 0x13, "${:02X}", opcode);
+        }
+    }
+
+    #[test]
+    fn high_byte_stores_across_a_page_replace_the_high_byte() {
+        let cases = [
+            (0x9E, 0x05, 0x20), // SHX $12F0,Y
+            (0x9C, 0x20, 0x05), // SHY $12F0,X
+        ];
+        for &(opcode, x, y) in &cases {
+            let state = CpuState {
+                index_x: x,
+                index_y: y,
+                ..CpuState::default()
+            };
+            let mut cpu = run_undocumented(CpuVariant::Nmos6502, &[opcode, 0xF0, 0x12], &[], state);
+            assert_eq!(cpu.bus_mut().read(0x0110), 0x01, "${:02X}", opcode);
+            assert_eq!(cpu.bus_mut().read(0x1310), 0x00, "${:02X}", opcode);
+        }
+    }
+
+    #[test]
+    fn sha_ands_a_and_x_with_the_high_byte() {
+        let state = CpuState {
+            accumulator: 0x37,
+            index_x: 0xF5,
+            index_y: 0x20,
+            ..CpuState::default()
+        };
+        // SHA $12F0,Y crosses into $13xx: $35 & $13 = $11 lands at $1110.
+        let mut cpu = run_undocumented(CpuVariant::Nmos6502, &[0x9F, 0xF0, 0x12], &[], state);
+        assert_eq!(cpu.bus_mut().read(0x1110), 0x11);
+
+        // SHA ($10),Y through a pointer to $1200 stays in the page.
+        let memory = [(0x0010, 0x00), (0x0011, 0x12)];
+        let mut cpu = run_undocumented(CpuVariant::Nmos6502, &[0x93, 0x10], &memory, state);
+        assert_eq!(cpu.bus_mut().read(0x1220), 0x11);
+    }
+
+    #[test]
+    fn tas_sets_the_stack_pointer_before_storing() {
+        let state = CpuState {
+            accumulator: 0xF3,
+            index_x: 0x3F,
+            index_y: 0x10,
+            ..CpuState::default()
+        };
+        let mut cpu = run_undocumented(CpuVariant::Nmos6502, &[0x9B, 0x00, 0x12], &[], state);
+        assert_eq!(cpu.stack_pointer(), 0x33);
+        assert_eq!(cpu.bus_mut().read(0x1210), 0x13);
+    }
+
+    #[test]
+    fn las_ands_memory_with_the_stack_pointer() {
+        let state = CpuState {
+            index_y: 0x20,
+            stack_pointer: 0xF0,
+            ..CpuState::default()
+        };
+        let cpu = run_undocumented(
+            CpuVariant::Nmos6502,
+            &[0xBB, 0xF0, 0x12],
+            &[(0x1310, 0x9C)],
+            state,
+        );
+        let registers = (cpu.accumulator(), cpu.index_x(), cpu.stack_pointer());
+        assert_eq!(registers, (0x90, 0x90, 0x90));
+        assert_eq!(flags(&cpu), (true, false, false, false));
+    }
+
+    #[test]
+    fn xaa_and_lxa_use_the_magic_constant() {
+        let state = CpuState {
+            accumulator: 0x01,
+            index_x: 0x7F,
+            ..CpuState::default()
+        };
+        // ($01 | $EE) & $7F & $FF
+        let cpu = run_undocumented(CpuVariant::Nmos6502, &[0x8B, 0xFF], &[], state);
+        assert_eq!((cpu.accumulator(), cpu.index_x()), (0x6F, 0x7F));
+
+        // ($01 | $EE) & $F0, into both A and X
+        let cpu = run_undocumented(CpuVariant::Nmos6502, &[0xAB, 0xF0], &[], state);
+        assert_eq!((cpu.accumulator(), cpu.index_x()), (0xE0, 0xE0));
+        assert_eq!(flags(&cpu), (true, false, false, false));
+    }
+
+    #[test]
+    fn jmp_indirect_wraps_within_page_on_nmos() {
+        let memory = [(0x02FF, 0x34), (0x0300, 0x12), (0x0200, 0x56)];
+        for &variant in &[CpuVariant::Nmos6502, CpuVariant::Ricoh2A03] {
+            let mut cpu = cpu_with(variant, &[0x6C, 0xFF, 0x02], &memory);
+            cpu.step().unwrap();
+            assert_eq!(cpu.program_counter(), 0x5634);
         }
     }
+
+    #[test]
+    fn jmp_indirect_crosses_page_on_65c02() {
+        let memory = [(0x02FF, 0x34), (0x0300, 0x12), (0x0200, 0x56)];
+        let mut cpu = cpu_with(CpuVariant::Wdc65C02, &[0x6C, 0xFF, 0x02], &memory);
+        cpu.step().unwrap();
+        assert_eq!(cpu.program_counter(), 0x1234);
+    }
+
+    #[test]
+    fn zero_page_x_wraps_within_page_zero() {
+        let memory = [(0x0010, 0xAB), (0x0110, 0xCD)];
+        // LDX #$20; LDA $F0,X
+        let mut cpu = cpu_with(CpuVariant::Nmos6502, &[0xA2, 0x20, 0xB5, 0xF0], &memory);
+        cpu.step().unwrap();
+        cpu.step().unwrap();
+        assert_eq!(cpu.accumulator(), 0xAB);
+    }
+
+    #[test]
+    fn zero_page_y_wraps_within_page_zero() {
+        let memory = [(0x0004, 0xAB), (0x0104, 0xCD)];
+        // LDY #$05; LDX $FF,Y
+        let mut cpu = cpu_with(CpuVariant::Nmos6502, &[0xA0, 0x05, 0xB6, 0xFF], &memory);
+        cpu.step().unwrap();
+        cpu.step().unwrap();
+        assert_eq!(cpu.index_x(), 0xAB);
+    }
+
+    #[test]
+    fn indexed_indirect_pointer_wraps_within_page_zero() {
+        let memory = [
+            (0x00FF, 0x00),
+            (0x0000, 0x03),
+            (0x0100, 0x04),
+            (0x0300, 0xAB),
+        ];
+        // LDX #$01; LDA ($FE,X)
+        let mut cpu = cpu_with(CpuVariant::Nmos6502, &[0xA2, 0x01, 0xA1, 0xFE], &memory);
+        cpu.step().unwrap();
+        cpu.step().unwrap();
+        assert_eq!(cpu.accumulator(), 0xAB);
+    }
+
+    #[test]
+    fn indexed_indirect_index_wraps_within_page_zero() {
+        let memory = [(0x0010, 0x00), (0x0011, 0x03), (0x0300, 0xAB)];
+        // LDX #$20; LDA ($F0,X)
+        let mut cpu = cpu_with(CpuVariant::Nmos6502, &[0xA2, 0x20, 0xA1, 0xF0], &memory);
+        cpu.step().unwrap();
+        cpu.step().unwrap();
+        assert_eq!(cpu.accumulator(), 0xAB);
+    }
+
+    #[test]
+    fn indirect_indexed_pointer_wraps_within_page_zero() {
+        let memory = [
+            (0x00FF, 0x00),
+            (0x0000, 0x03),
+            (0x0100, 0x04),
+            (0x0302, 0xAB),
+        ];
+        // LDY #$02; LDA ($FF),Y
+        let mut cpu = cpu_with(CpuVariant::Nmos6502, &[0xA0, 0x02, 0xB1, 0xFF], &memory);
+        cpu.step().unwrap();
+        cpu.step().unwrap();
+        assert_eq!(cpu.accumulator(), 0xAB);
+    }
 }
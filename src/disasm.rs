@@ -0,0 +1,437 @@
+//! Disassembly of single instructions and address ranges.
+
+use crate::bus::Bus;
+use crate::cpu::CpuVariant;
+use crate::opcode::{AddressingMode, Instruction, Opcode};
+use core::fmt;
+use core::ops::RangeInclusive;
+
+/// Memory that can be read for disassembly: a bus, borrowed mutably and read
+/// through `Bus::peek`, or a byte slice paired with the address of its first
+/// byte.
+pub trait Source {
+    /// Reads a byte without side effects, or `None` where the source has no
+    /// byte.
+    fn peek(&mut self, address: u16) -> Option<u8>;
+}
+
+impl<B: Bus + ?Sized> Source for &mut B {
+    fn peek(&mut self, address: u16) -> Option<u8> {
+        Some(Bus::peek(*self, address))
+    }
+}
+
+impl Source for (u16, &[u8]) {
+    fn peek(&mut self, address: u16) -> Option<u8> {
+        let (base, bytes) = *self;
+        bytes.get(address.wrapping_sub(base) as usize).copied()
+    }
+}
+
+/// The source ran out part way through the instruction at `address`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Truncated {
+    /// Address of the opcode, or of the missing opcode byte.
+    pub address: u16,
+}
+
+impl fmt::Display for Truncated {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "instruction at ${:04X} is cut off", self.address)
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for Truncated {}
+
+/// A decoded operand. It displays in the usual assembler syntax.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Operand {
+    /// No operand.
+    None,
+    /// `A`
+    Accumulator,
+    /// `#$nn`
+    Immediate(u8),
+    /// `$nn`
+    ZeroPage(u8),
+    /// `$nn,X`
+    ZeroPageX(u8),
+    /// `$nn,Y`
+    ZeroPageY(u8),
+    /// `$nnnn`
+    Absolute(u16),
+    /// `$nnnn,X`
+    AbsoluteX(u16),
+    /// `$nnnn,Y`
+    AbsoluteY(u16),
+    /// `($nnnn)`
+    Indirect(u16),
+    /// `($nn,X)`
+    IndexedIndirect(u8),
+    /// `($nn),Y`
+    IndirectIndexed(u8),
+    /// `($nn)`, on the 65C02.
+    ZeroPageIndirect(u8),
+    /// `($nnnn,X)`, on the 65C02.
+    AbsoluteIndexedIndirect(u16),
+    /// A branch, shown as its resolved target.
+    Relative {
+        /// Where the branch goes when taken.
+        target: u16,
+    },
+    /// The 65C02 BBR and BBS: a zero page byte to test and a branch.
+    ZeroPageRelative {
+        /// The zero page address tested.
+        address: u8,
+        /// Where the branch goes when taken.
+        target: u16,
+    },
+}
+
+impl fmt::Display for Operand {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Operand::None => Ok(()),
+            Operand::Accumulator => write!(f, "A"),
+            Operand::Immediate(value) => write!(f, "#${:02X}", value),
+            Operand::ZeroPage(address) => write!(f, "${:02X}", address),
+            Operand::ZeroPageX(address) => write!(f, "${:02X},X", address),
+            Operand::ZeroPageY(address) => write!(f, "${:02X},Y", address),
+            Operand::Absolute(address) => write!(f, "${:04X}", address),
+            Operand::AbsoluteX(address) => write!(f, "${:04X},X", address),
+            Operand::AbsoluteY(address) => write!(f, "${:04X},Y", address),
+            Operand::Indirect(address) => write!(f, "(${:04X})", address),
+            Operand::IndexedIndirect(address) => write!(f, "(${:02X},X)", address),
+            Operand::IndirectIndexed(address) => write!(f, "(${:02X}),Y", address),
+            Operand::ZeroPageIndirect(address) => write!(f, "(${:02X})", address),
+            Operand::AbsoluteIndexedIndirect(address) => write!(f, "(${:04X},X)", address),
+            Operand::Relative { target } => write!(f, "${:04X}", target),
+            Operand::ZeroPageRelative { address, target } => {
+                write!(f, "${:02X},${:04X}", address, target)
+            }
+        }
+    }
+}
+
+/// One decoded instruction.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct DecodedInstruction {
+    /// Address of the opcode.
+    pub address: u16,
+    /// The opcode table entry.
+    pub opcode: Opcode,
+    /// The decoded operand.
+    pub operand: Operand,
+    bytes: [u8; 3],
+}
+
+impl DecodedInstruction {
+    /// The instruction's bytes, opcode first.
+    pub fn bytes(&self) -> &[u8] {
+        &self.bytes[..self.opcode.bytes as usize]
+    }
+
+    /// The mnemonic, in upper case.
+    pub fn mnemonic(&self) -> &'static str {
+        self.opcode.mnemonic()
+    }
+
+    /// Where control can go other than the next instruction, when that is
+    /// known without executing: branches and absolute JMP and JSR.
+    pub fn target(&self) -> Option<u16> {
+        match (self.opcode.instruction, self.operand) {
+            (_, Operand::Relative { target }) => Some(target),
+            (_, Operand::ZeroPageRelative { target, .. }) => Some(target),
+            (Instruction::Jmp, Operand::Absolute(address)) => Some(address),
+            (Instruction::Jsr, Operand::Absolute(address)) => Some(address),
+            _ => None,
+        }
+    }
+}
+
+// `{}` gives the instruction alone, `{:#}` a listing line with the address
+// and raw bytes.
+impl fmt::Display for DecodedInstruction {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if f.alternate() {
+            write!(f, "{:04X} ", self.address)?;
+            for index in 0..3 {
+                match self.bytes().get(index) {
+                    Some(byte) => write!(f, " {:02X}", byte)?,
+                    None => write!(f, "   ")?,
+                }
+            }
+            write!(f, "  ")?;
+        }
+
+        match self.operand {
+            Operand::None => write!(f, "{}", self.mnemonic()),
+            operand => write!(f, "{} {}", self.mnemonic(), operand),
+        }
+    }
+}
+
+/// Decodes the instruction at `address` with the NMOS opcode table. Fails if
+/// the source runs out before the instruction ends.
+pub fn disassemble<S: Source>(source: S, address: u16) -> Result<DecodedInstruction, Truncated> {
+    disassemble_variant(source, address, CpuVariant::Nmos6502)
+}
+
+/// Decodes the instruction at `address` with the opcode table of `variant`.
+pub fn disassemble_variant<S: Source>(
+    mut source: S,
+    address: u16,
+    variant: CpuVariant,
+) -> Result<DecodedInstruction, Truncated> {
+    decode(&mut source, address, variant)
+}
+
+fn decode<S: Source>(
+    source: &mut S,
+    address: u16,
+    variant: CpuVariant,
+) -> Result<DecodedInstruction, Truncated> {
+    let mut peek = |offset: u16| {
+        source
+            .peek(address.wrapping_add(offset))
+            .ok_or(Truncated { address })
+    };
+
+    let byte = peek(0)?;
+    let opcode = variant.opcodes()[byte as usize];
+
+    let mut bytes = [byte, 0, 0];
+    for (offset, slot) in (1..).zip(&mut bytes[1..opcode.bytes as usize]) {
+        *slot = peek(offset)?;
+    }
+
+    let low = bytes[1];
+    let word = u16::from_le_bytes([bytes[1], bytes[2]]);
+    let next = address.wrapping_add(opcode.bytes as u16);
+
+    let operand = match opcode.mode {
+        AddressingMode::Implicit => Operand::None,
+        AddressingMode::Accumulator => Operand::Accumulator,
+        AddressingMode::Immediate => Operand::Immediate(low),
+        AddressingMode::ZeroPage => Operand::ZeroPage(low),
+        AddressingMode::ZeroPageX => Operand::ZeroPageX(low),
+        AddressingMode::ZeroPageY => Operand::ZeroPageY(low),
+        AddressingMode::Absolute => Operand::Absolute(word),
+        AddressingMode::AbsoluteX => Operand::AbsoluteX(word),
+        AddressingMode::AbsoluteY => Operand::AbsoluteY(word),
+        AddressingMode::Indirect => Operand::Indirect(word),
+        AddressingMode::IndexedIndirect => Operand::IndexedIndirect(low),
+        AddressingMode::IndirectIndexed => Operand::IndirectIndexed(low),
+        AddressingMode::ZeroPageIndirect => Operand::ZeroPageIndirect(low),
+        AddressingMode::AbsoluteIndexedIndirect => Operand::AbsoluteIndexedIndirect(word),
+        AddressingMode::Relative => Operand::Relative {
+            target: next.wrapping_add(low as i8 as u16),
+        },
+        AddressingMode::ZeroPageRelative => Operand::ZeroPageRelative {
+            address: low,
+            target: next.wrapping_add(bytes[2] as i8 as u16),
+        },
+    };
+
+    Ok(DecodedInstruction {
+        address,
+        opcode,
+        operand,
+        bytes,
+    })
+}
+
+/// Decodes instructions back to back from the start of `range`, treating
+/// every byte as code. The last instruction may extend past the range. If the
+/// source runs out first, the sweep ends with a `Truncated` error.
+pub fn sweep<S: Source>(source: S, range: RangeInclusive<u16>, variant: CpuVariant) -> Sweep<S> {
+    Sweep {
+        source,
+        next: Some(*range.start()),
+        end: *range.end(),
+        variant,
+    }
+}
+
+/// The iterator returned by `sweep`.
+pub struct Sweep<S: Source> {
+    source: S,
+    next: Option<u16>,
+    end: u16,
+    variant: CpuVariant,
+}
+
+impl<S: Source> Iterator for Sweep<S> {
+    type Item = Result<DecodedInstruction, Truncated>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let address = self.next.filter(|&address| address <= self.end)?;
+        let decoded = decode(&mut self.source, address, self.variant);
+        self.next = match decoded {
+            Ok(instruction) => address.checked_add(instruction.opcode.bytes as u16),
+            Err(_) => None,
+        };
+        Some(decoded)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bus::Ram;
+
+    // Any read is a side effect the disassembler must not cause.
+    struct PeekOnlyBus(Ram);
+
+    impl Bus for PeekOnlyBus {
+        fn read(&mut self, address: u16) -> u8 {
+            panic!("read ${:04X} while disassembling", address)
+        }
+
+        fn write(&mut self, address: u16, _value: u8) {
+            panic!("wrote ${:04X} while disassembling", address)
+        }
+
+        fn peek(&mut self, address: u16) -> u8 {
+            self.0.read(address)
+        }
+    }
+
+    fn decode_at(bytes: &[u8], address: u16, variant: CpuVariant) -> DecodedInstruction {
+        let mut ram = Ram::new();
+        ram.load(address, bytes);
+        disassemble_variant(&mut PeekOnlyBus(ram), address, variant).unwrap()
+    }
+
+    #[test]
+    fn operand_display_forms() {
+        let cases = [
+            (Operand::None, ""),
+            (Operand::Accumulator, "A"),
+            (Operand::Immediate(0x0F), "#$0F"),
+            (Operand::ZeroPage(0x10), "$10"),
+            (Operand::ZeroPageX(0x10), "$10,X"),
+            (Operand::ZeroPageY(0x10), "$10,Y"),
+            (Operand::Absolute(0x1234), "$1234"),
+            (Operand::AbsoluteX(0x1234), "$1234,X"),
+            (Operand::AbsoluteY(0x1234), "$1234,Y"),
+            (Operand::Indirect(0x1234), "($1234)"),
+            (Operand::IndexedIndirect(0x10), "($10,X)"),
+            (Operand::IndirectIndexed(0x10), "($10),Y"),
+            (Operand::ZeroPageIndirect(0x10), "($10)"),
+            (Operand::AbsoluteIndexedIndirect(0x1234), "($1234,X)"),
+            (Operand::Relative { target: 0x0412 }, "$0412"),
+            (
+                Operand::ZeroPageRelative {
+                    address: 0x10,
+                    target: 0x0412,
+                },
+                "$10,$0412",
+            ),
+        ];
+        for (operand, expected) in cases.iter() {
+            assert_eq!(operand.to_string(), *expected);
+        }
+    }
+
+    #[test]
+    fn branch_targets() {
+        let forward = decode_at(&[0xD0, 0x10], 0x0400, CpuVariant::Nmos6502);
+        assert_eq!(forward.target(), Some(0x0412));
+        assert_eq!(forward.to_string(), "BNE $0412");
+
+        let backward = decode_at(&[0xF0, 0xFE], 0x0400, CpuVariant::Nmos6502);
+        assert_eq!(backward.target(), Some(0x0400));
+    }
+
+    #[test]
+    fn bbr_target_is_relative_to_the_next_instruction() {
+        let instruction = decode_at(&[0x1F, 0x12, 0xFB], 0x0400, CpuVariant::Wdc65C02);
+        assert_eq!(instruction.target(), Some(0x03FE));
+        assert_eq!(instruction.to_string(), "BBR1 $12,$03FE");
+    }
+
+    #[test]
+    fn alternate_form_is_a_listing_line() {
+        let cases: [(&[u8], &str); 3] = [
+            (&[0xEA], "0400  EA        NOP"),
+            (&[0xA9, 0x42], "0400  A9 42     LDA #$42"),
+            (&[0x4C, 0x34, 0x12], "0400  4C 34 12  JMP $1234"),
+        ];
+        for (bytes, expected) in cases.iter() {
+            let instruction = decode_at(bytes, 0x0400, CpuVariant::Nmos6502);
+            assert_eq!(format!("{:#}", instruction), *expected);
+        }
+    }
+
+    #[test]
+    fn illegal_opcodes() {
+        let jam = disassemble((0x8000, &[0x02][..]), 0x8000).unwrap();
+        assert!(!jam.opcode.documented);
+        assert_eq!(format!("{:#}", jam), "8000  02        JAM");
+
+        let lax = disassemble((0x8000, &[0xA7, 0x10][..]), 0x8000).unwrap();
+        assert!(!lax.opcode.documented);
+        assert_eq!(lax.to_string(), "LAX $10");
+
+        // The 65C02 turns the same byte into a two-byte NOP.
+        let source = (0x8000, &[0x02, 0x10][..]);
+        let cmos = disassemble_variant(source, 0x8000, CpuVariant::Wdc65C02).unwrap();
+        assert_eq!(format!("{:#}", cmos), "8000  02 10     NOP #$10");
+    }
+
+    #[test]
+    fn slices_are_addressed_from_their_base() {
+        let rom: &[u8] = &[0xEA, 0xA9, 0x42];
+        let load = disassemble((0xC000, rom), 0xC001).unwrap();
+        assert_eq!(format!("{:#}", load), "C001  A9 42     LDA #$42");
+
+        let outside = Truncated { address: 0x8000 };
+        assert_eq!(disassemble((0xC000, rom), 0x8000), Err(outside));
+        let after = Truncated { address: 0xC003 };
+        assert_eq!(disassemble((0xC000, rom), 0xC003), Err(after));
+    }
+
+    #[test]
+    fn cut_off_instructions_are_reported() {
+        let rom: &[u8] = &[0xEA, 0x4C, 0x00];
+        let truncated = Truncated { address: 0xC001 };
+        assert_eq!(disassemble((0xC000, rom), 0xC001), Err(truncated));
+
+        let listing = sweep((0xC000, rom), 0xC000..=0xFFFF, CpuVariant::Nmos6502)
+            .map(|decoded| decoded.map(|instruction| instruction.to_string()))
+            .collect::<Vec<_>>();
+        assert_eq!(listing, [Ok("NOP".to_string()), Err(truncated)]);
+    }
+
+    #[test]
+    fn sweep_stops_at_the_end_of_the_range() {
+        let code: &[u8] = &[0xA9, 0x01, 0xEA, 0x4C, 0x00, 0x00];
+        let addresses = |range| {
+            sweep((0x0000, code), range, CpuVariant::Nmos6502)
+                .map(|decoded| decoded.unwrap().address)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(addresses(0..=2), [0, 2]);
+        // The last instruction may run past the range.
+        assert_eq!(addresses(0..=3), [0, 2, 3]);
+    }
+
+    #[test]
+    fn sweep_stops_at_the_top_of_memory() {
+        let mut ram = Ram::new();
+        ram.load(0xFFFC, &[0xEA, 0xEA, 0xEA, 0xEA]);
+        let mut bus = PeekOnlyBus(ram);
+        assert_eq!(
+            sweep(&mut bus, 0xFFFC..=0xFFFF, CpuVariant::Nmos6502).count(),
+            4
+        );
+
+        bus.0.load(0xFFFE, &[0xA9, 0x00]);
+        let last = sweep(&mut bus, 0xFFFE..=0xFFFF, CpuVariant::Nmos6502)
+            .map(|decoded| decoded.unwrap().address)
+            .collect::<Vec<_>>();
+        assert_eq!(last, [0xFFFE]);
+    }
+}
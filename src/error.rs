@@ -0,0 +1,59 @@
+//! Errors that stop instruction execution.
+
+use core::fmt;
+
+/// Why the CPU could not complete an instruction.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CpuError {
+    /// An undocumented opcode was fetched while they are disabled. Nothing is
+    /// executed and PC is left on the opcode.
+    IllegalOpcode {
+        /// Address of the opcode.
+        pc: u16,
+        /// The opcode byte.
+        opcode: u8,
+    },
+    /// A JAM or STP opcode stopped the CPU. Every later step reports this
+    /// again until a reset.
+    Halted {
+        /// Address of the opcode.
+        pc: u16,
+        /// The opcode byte.
+        opcode: u8,
+    },
+    /// The bus reported a fault through `Bus::take_fault`. The instruction
+    /// still ran to completion.
+    BusFault {
+        /// Address of the instruction's opcode.
+        pc: u16,
+        /// The opcode byte.
+        opcode: u8,
+        /// The address the bus could not service.
+        address: u16,
+    },
+}
+
+impl fmt::Display for CpuError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            CpuError::IllegalOpcode { pc, opcode } => {
+                write!(f, "illegal opcode ${:02X} at ${:04X}", opcode, pc)
+            }
+            CpuError::Halted { pc, opcode } => {
+                write!(f, "CPU halted by opcode ${:02X} at ${:04X}", opcode, pc)
+            }
+            CpuError::BusFault {
+                pc,
+                opcode,
+                address,
+            } => write!(
+                f,
+                "bus fault at ${:04X} during opcode ${:02X} at ${:04X}",
+                address, opcode, pc
+            ),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for CpuError {}
@@ -0,0 +1,38 @@
+//! A cycle-accurate 6502 emulator core covering the NMOS 6502, the Ricoh
+//! 2A03 used in the NES and the WDC 65C02.
+//!
+//! [`Machine`] is the simplest entry point. [`Cpu`] and the [`Bus`] trait are
+//! available for embedding the core behind custom memory maps.
+//!
+//! The core builds without `std` or `alloc`. The `alloc` feature adds memory
+//! watches. The default `std` feature implies it and adds the `trace` module,
+//! instruction tracing on [`Cpu`] and `std::error::Error` for [`CpuError`].
+
+#![warn(missing_docs)]
+// Unit tests link std in every configuration for its prelude and harness.
+#![cfg_attr(not(any(feature = "std", test)), no_std)]
+
+#[cfg(feature = "alloc")]
+extern crate alloc;
+
+pub mod bus;
+pub mod cpu;
+pub mod disasm;
+pub mod error;
+pub mod machine;
+pub mod opcode;
+pub mod status;
+#[cfg(feature = "std")]
+pub mod trace;
+pub mod watch;
+
+pub use bus::{Bus, Ram, RamPattern};
+pub use cpu::{Cpu, CpuState, CpuVariant, Interrupt, StepInfo, StopConditions, StopReason};
+pub use disasm::{disassemble, DecodedInstruction, Operand, Truncated};
+pub use error::CpuError;
+pub use machine::Machine;
+pub use opcode::{AddressingMode, Instruction, Opcode};
+pub use status::StatusFlags;
+#[cfg(feature = "alloc")]
+pub use watch::WatchId;
+pub use watch::{AccessKind, MemoryAccess};
@@ -0,0 +1,152 @@
+//! A machine bundling a CPU with its bus.
+
+use crate::bus::{Bus, Ram};
+use crate::cpu::{Cpu, CpuState, CpuVariant, StepInfo, StopConditions, StopReason};
+use crate::error::CpuError;
+#[cfg(feature = "alloc")]
+use crate::watch::{AccessKind, MemoryAccess, WatchId};
+#[cfg(feature = "alloc")]
+use core::ops::RangeInclusive;
+
+/// A CPU wired to its bus, for embedding the emulator behind one type.
+pub struct Machine<B: Bus = Ram> {
+    cpu: Cpu<B>,
+}
+
+impl Machine {
+    /// Creates a machine with 64 KiB of zeroed RAM.
+    pub fn new(variant: CpuVariant) -> Self {
+        Machine::with_bus(variant, Ram::new())
+    }
+}
+
+impl<B: Bus> Machine<B> {
+    /// Creates a machine around a caller-supplied bus.
+    pub fn with_bus(variant: CpuVariant, bus: B) -> Self {
+        Machine {
+            cpu: Cpu::with_variant(variant, bus),
+        }
+    }
+
+    /// Writes `data` through the bus starting at `address`, wrapping at $FFFF.
+    pub fn load(&mut self, address: u16, data: &[u8]) {
+        let bus = self.cpu.bus_mut();
+        for (offset, &value) in data.iter().enumerate() {
+            bus.write(address.wrapping_add(offset as u16), value);
+        }
+    }
+
+    /// Clears the registers and runs the reset sequence.
+    pub fn power_on(&mut self) {
+        self.cpu.power_on();
+    }
+
+    /// Runs the reset sequence, keeping A, X and Y.
+    pub fn reset(&mut self) {
+        self.cpu.reset();
+    }
+
+    /// Signals an edge on the NMI input.
+    pub fn nmi(&mut self) {
+        self.cpu.nmi();
+    }
+
+    /// Drives the level-triggered IRQ input.
+    pub fn set_irq(&mut self, asserted: bool) {
+        self.cpu.set_irq(asserted);
+    }
+
+    /// Holds the CPU off the bus for `cycles` cycles before its next
+    /// instruction, as DMA does.
+    pub fn stall(&mut self, cycles: u32) {
+        self.cpu.stall(cycles);
+    }
+
+    /// Runs one instruction or interrupt sequence.
+    pub fn step(&mut self) -> Result<StepInfo, CpuError> {
+        self.cpu.step()
+    }
+
+    /// Runs one bus cycle, returning the step summary when an instruction
+    /// completes on it.
+    pub fn tick(&mut self) -> Result<Option<StepInfo>, CpuError> {
+        self.cpu.tick()
+    }
+
+    /// Runs until one of the configured stop conditions is met.
+    pub fn run(&mut self) -> StopReason {
+        self.cpu.run()
+    }
+
+    /// Runs for at least `cycles` cycles, stopping early on a stop condition.
+    pub fn run_for_cycles(&mut self, cycles: u64) -> StopReason {
+        self.cpu.run_for_cycles(cycles)
+    }
+
+    /// Chooses when `run` and `run_for_cycles` return.
+    pub fn set_stop_conditions(&mut self, conditions: StopConditions) {
+        self.cpu.set_stop_conditions(conditions);
+    }
+
+    /// Calls `callback` for every bus access of `kind` within `range`.
+    #[cfg(feature = "alloc")]
+    pub fn add_watch<F>(
+        &mut self,
+        kind: AccessKind,
+        range: RangeInclusive<u16>,
+        callback: F,
+    ) -> WatchId
+    where
+        F: FnMut(&MemoryAccess) + Send + 'static,
+    {
+        self.cpu.add_watch(kind, range, callback)
+    }
+
+    /// Makes `run` stop with `StopReason::Watchpoint` on a matching access.
+    #[cfg(feature = "alloc")]
+    pub fn add_watchpoint(&mut self, kind: AccessKind, range: RangeInclusive<u16>) -> WatchId {
+        self.cpu.add_watchpoint(kind, range)
+    }
+
+    /// Removes a watch or watchpoint.
+    #[cfg(feature = "alloc")]
+    pub fn remove_watch(&mut self, id: WatchId) {
+        self.cpu.remove_watch(id);
+    }
+
+    /// Returns the programmer-visible registers.
+    pub fn state(&self) -> CpuState {
+        self.cpu.state()
+    }
+
+    /// Replaces the programmer-visible registers.
+    pub fn set_state(&mut self, state: CpuState) {
+        self.cpu.set_state(state);
+    }
+
+    /// Returns the number of cycles run since the last power-on, or since
+    /// the machine was created if it has not been powered on.
+    pub fn cycles(&self) -> u64 {
+        self.cpu.cycles()
+    }
+
+    /// Borrows the CPU, for anything the machine does not forward.
+    pub fn cpu(&self) -> &Cpu<B> {
+        &self.cpu
+    }
+
+    /// Borrows the CPU mutably.
+    pub fn cpu_mut(&mut self) -> &mut Cpu<B> {
+        &mut self.cpu
+    }
+
+    /// Borrows the bus.
+    pub fn bus(&self) -> &B {
+        self.cpu.bus()
+    }
+
+    /// Borrows the bus mutably. Accesses made through it do not take cycles.
+    pub fn bus_mut(&mut self) -> &mut B {
+        self.cpu.bus_mut()
+    }
+}
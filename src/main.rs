@@ -1,8 +1,12 @@
-mod cpu;
-
-use cpu::Cpu;
+use goomba::{CpuVariant, Machine, StopConditions};
 
 fn main() {
-    let mut cpu = Cpu::new();
-    cpu.run();
+    let mut machine = Machine::new(CpuVariant::Ricoh2A03);
+    machine.set_stop_conditions(StopConditions {
+        on_break: true,
+        on_self_jump: true,
+        ..StopConditions::default()
+    });
+    machine.power_on();
+    println!("{:?}", machine.run());
 }
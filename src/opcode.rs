@@ -0,0 +1,956 @@
+//! Opcode tables for the NMOS 6502 and the 65C02.
+
+/// How an instruction finds its operand.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AddressingMode {
+    /// No operand.
+    Implicit,
+    /// Operates on A.
+    Accumulator,
+    /// `#$nn`
+    Immediate,
+    /// `$nn`
+    ZeroPage,
+    /// `$nn,X`
+    ZeroPageX,
+    /// `$nn,Y`
+    ZeroPageY,
+    /// A signed branch offset.
+    Relative,
+    /// `$nnnn`
+    Absolute,
+    /// `$nnnn,X`
+    AbsoluteX,
+    /// `$nnnn,Y`
+    AbsoluteY,
+    /// `($nnnn)`
+    Indirect,
+    /// `($nn,X)`
+    IndexedIndirect,
+    /// `($nn),Y`
+    IndirectIndexed,
+    /// `($nn)`, on the 65C02.
+    ZeroPageIndirect,
+    /// `($nnnn,X)`, on the 65C02.
+    AbsoluteIndexedIndirect,
+    /// A zero page address and a branch offset, for BBR and BBS.
+    ZeroPageRelative,
+}
+
+/// An instruction, independent of addressing mode. The 65C02 bit
+/// instructions carry their bit number.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Instruction {
+    /// Add with carry.
+    Adc,
+    /// AND with A.
+    And,
+    /// Arithmetic shift left.
+    Asl,
+    /// Branch if carry clear.
+    Bcc,
+    /// Branch if carry set.
+    Bcs,
+    /// Branch if equal (Z set).
+    Beq,
+    /// Test bits against A.
+    Bit,
+    /// Branch if minus (N set).
+    Bmi,
+    /// Branch if not equal (Z clear).
+    Bne,
+    /// Branch if plus (N clear).
+    Bpl,
+    /// Software interrupt.
+    Brk,
+    /// Branch if overflow clear.
+    Bvc,
+    /// Branch if overflow set.
+    Bvs,
+    /// Clear carry.
+    Clc,
+    /// Clear decimal mode.
+    Cld,
+    /// Clear interrupt disable.
+    Cli,
+    /// Clear overflow.
+    Clv,
+    /// Compare with A.
+    Cmp,
+    /// Compare with X.
+    Cpx,
+    /// Compare with Y.
+    Cpy,
+    /// Decrement memory.
+    Dec,
+    /// Decrement X.
+    Dex,
+    /// Decrement Y.
+    Dey,
+    /// Exclusive OR with A.
+    Eor,
+    /// Increment memory, or A on the 65C02.
+    Inc,
+    /// Increment X.
+    Inx,
+    /// Increment Y.
+    Iny,
+    /// Jump.
+    Jmp,
+    /// Jump to subroutine.
+    Jsr,
+    /// Load A.
+    Lda,
+    /// Load X.
+    Ldx,
+    /// Load Y.
+    Ldy,
+    /// Logical shift right.
+    Lsr,
+    /// No operation, including the undocumented forms that read memory.
+    Nop,
+    /// OR with A.
+    Ora,
+    /// Push A.
+    Pha,
+    /// Push P.
+    Php,
+    /// Pull A.
+    Pla,
+    /// Pull P.
+    Plp,
+    /// Rotate left through carry.
+    Rol,
+    /// Rotate right through carry.
+    Ror,
+    /// Return from interrupt.
+    Rti,
+    /// Return from subroutine.
+    Rts,
+    /// Subtract with borrow.
+    Sbc,
+    /// Set carry.
+    Sec,
+    /// Set decimal mode.
+    Sed,
+    /// Set interrupt disable.
+    Sei,
+    /// Store A.
+    Sta,
+    /// Store X.
+    Stx,
+    /// Store Y.
+    Sty,
+    /// Transfer A to X.
+    Tax,
+    /// Transfer A to Y.
+    Tay,
+    /// Transfer SP to X.
+    Tsx,
+    /// Transfer X to A.
+    Txa,
+    /// Transfer X to SP.
+    Txs,
+    /// Transfer Y to A.
+    Tya,
+    /// Undocumented: AND then LSR A.
+    Alr,
+    /// Undocumented: AND, copying N into C.
+    Anc,
+    /// Undocumented: AND then ROR A, with odd C and V.
+    Arr,
+    /// Undocumented: DEC then CMP.
+    Dcp,
+    /// Undocumented: INC then SBC.
+    Isc,
+    /// Undocumented: halts the CPU.
+    Jam,
+    /// Undocumented: memory AND SP into A, X and SP.
+    Las,
+    /// Undocumented: load A and X.
+    Lax,
+    /// Undocumented: unstable AND immediate into A and X.
+    Lxa,
+    /// Undocumented: ROL then AND.
+    Rla,
+    /// Undocumented: ROR then ADC.
+    Rra,
+    /// Undocumented: store A AND X.
+    Sax,
+    /// Undocumented: (A AND X) minus immediate into X.
+    Sbx,
+    /// Undocumented: store A AND X AND the address high byte plus one.
+    Sha,
+    /// Undocumented: store X AND the address high byte plus one.
+    Shx,
+    /// Undocumented: store Y AND the address high byte plus one.
+    Shy,
+    /// Undocumented: ASL then ORA.
+    Slo,
+    /// Undocumented: LSR then EOR.
+    Sre,
+    /// Undocumented: A AND X into SP, then store as SHA.
+    Tas,
+    /// Undocumented: unstable X AND immediate into A.
+    Xaa,
+    /// 65C02: branch if the given zero page bit is clear.
+    Bbr(u8),
+    /// 65C02: branch if the given zero page bit is set.
+    Bbs(u8),
+    /// 65C02: branch always.
+    Bra,
+    /// 65C02: push X.
+    Phx,
+    /// 65C02: push Y.
+    Phy,
+    /// 65C02: pull X.
+    Plx,
+    /// 65C02: pull Y.
+    Ply,
+    /// 65C02: clear the given zero page bit.
+    Rmb(u8),
+    /// 65C02: set the given zero page bit.
+    Smb(u8),
+    /// 65C02: stop the clock until reset.
+    Stp,
+    /// 65C02: store zero.
+    Stz,
+    /// 65C02: test and reset bits.
+    Trb,
+    /// 65C02: test and set bits.
+    Tsb,
+    /// 65C02: wait for an interrupt.
+    Wai,
+}
+
+impl Instruction {
+    /// The mnemonic, in upper case, with the bit number for BBR, BBS, RMB and
+    /// SMB.
+    pub fn mnemonic(&self) -> &'static str {
+        match self {
+            Instruction::Adc => "ADC",
+            Instruction::And => "AND",
+            Instruction::Asl => "ASL",
+            Instruction::Bcc => "BCC",
+            Instruction::Bcs => "BCS",
+            Instruction::Beq => "BEQ",
+            Instruction::Bit => "BIT",
+            Instruction::Bmi => "BMI",
+            Instruction::Bne => "BNE",
+            Instruction::Bpl => "BPL",
+            Instruction::Brk => "BRK",
+            Instruction::Bvc => "BVC",
+            Instruction::Bvs => "BVS",
+            Instruction::Clc => "CLC",
+            Instruction::Cld => "CLD",
+            Instruction::Cli => "CLI",
+            Instruction::Clv => "CLV",
+            Instruction::Cmp => "CMP",
+            Instruction::Cpx => "CPX",
+            Instruction::Cpy => "CPY",
+            Instruction::Dec => "DEC",
+            Instruction::Dex => "DEX",
+            Instruction::Dey => "DEY",
+            Instruction::Eor => "EOR",
+            Instruction::Inc => "INC",
+            Instruction::Inx => "INX",
+            Instruction::Iny => "INY",
+            Instruction::Jmp => "JMP",
+            Instruction::Jsr => "JSR",
+            Instruction::Lda => "LDA",
+            Instruction::Ldx => "LDX",
+            Instruction::Ldy => "LDY",
+            Instruction::Lsr => "LSR",
+            Instruction::Nop => "NOP",
+            Instruction::Ora => "ORA",
+            Instruction::Pha => "PHA",
+            Instruction::Php => "PHP",
+            Instruction::Pla => "PLA",
+            Instruction::Plp => "PLP",
+            Instruction::Rol => "ROL",
+            Instruction::Ror => "ROR",
+            Instruction::Rti => "RTI",
+            Instruction::Rts => "RTS",
+            Instruction::Sbc => "SBC",
+            Instruction::Sec => "SEC",
+            Instruction::Sed => "SED",
+            Instruction::Sei => "SEI",
+            Instruction::Sta => "STA",
+            Instruction::Stx => "STX",
+            Instruction::Sty => "STY",
+            Instruction::Tax => "TAX",
+            Instruction::Tay => "TAY",
+            Instruction::Tsx => "TSX",
+            Instruction::Txa => "TXA",
+            Instruction::Txs => "TXS",
+            Instruction::Tya => "TYA",
+            Instruction::Alr => "ALR",
+            Instruction::Anc => "ANC",
+            Instruction::Arr => "ARR",
+            Instruction::Dcp => "DCP",
+            Instruction::Isc => "ISC",
+            Instruction::Jam => "JAM",
+            Instruction::Las => "LAS",
+            Instruction::Lax => "LAX",
+            Instruction::Lxa => "LXA",
+            Instruction::Rla => "RLA",
+            Instruction::Rra => "RRA",
+            Instruction::Sax => "SAX",
+            Instruction::Sbx => "SBX",
+            Instruction::Sha => "SHA",
+            Instruction::Shx => "SHX",
+            Instruction::Shy => "SHY",
+            Instruction::Slo => "SLO",
+            Instruction::Sre => "SRE",
+            Instruction::Tas => "TAS",
+            Instruction::Xaa => "XAA",
+            // Only the low three bits select a bit, as in the opcode itself.
+            Instruction::Bbr(bit) => [
+                "BBR0", "BBR1", "BBR2", "BBR3", "BBR4", "BBR5", "BBR6", "BBR7",
+            ][(*bit & 7) as usize],
+            Instruction::Bbs(bit) => [
+                "BBS0", "BBS1", "BBS2", "BBS3", "BBS4", "BBS5", "BBS6", "BBS7",
+            ][(*bit & 7) as usize],
+            Instruction::Bra => "BRA",
+            Instruction::Phx => "PHX",
+            Instruction::Phy => "PHY",
+            Instruction::Plx => "PLX",
+            Instruction::Ply => "PLY",
+            Instruction::Rmb(bit) => [
+                "RMB0", "RMB1", "RMB2", "RMB3", "RMB4", "RMB5", "RMB6", "RMB7",
+            ][(*bit & 7) as usize],
+            Instruction::Smb(bit) => [
+                "SMB0", "SMB1", "SMB2", "SMB3", "SMB4", "SMB5", "SMB6", "SMB7",
+            ][(*bit & 7) as usize],
+            Instruction::Stp => "STP",
+            Instruction::Stz => "STZ",
+            Instruction::Trb => "TRB",
+            Instruction::Tsb => "TSB",
+            Instruction::Wai => "WAI",
+        }
+    }
+}
+
+/// One opcode table entry.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Opcode {
+    /// What the opcode does.
+    pub instruction: Instruction,
+    /// How it addresses its operand.
+    pub mode: AddressingMode,
+    /// Length including the opcode byte.
+    pub bytes: u8,
+    /// Base cycle count, before page-crossing and branch penalties.
+    pub cycles: u8,
+    /// Whether the manufacturer documents the opcode.
+    pub documented: bool,
+}
+
+impl Opcode {
+    /// A documented opcode.
+    pub const fn new(
+        instruction: Instruction,
+        mode: AddressingMode,
+        bytes: u8,
+        cycles: u8,
+    ) -> Self {
+        Opcode {
+            instruction,
+            mode,
+            bytes,
+            cycles,
+            documented: true,
+        }
+    }
+
+    /// An undocumented opcode.
+    pub const fn undocumented(
+        instruction: Instruction,
+        mode: AddressingMode,
+        bytes: u8,
+        cycles: u8,
+    ) -> Self {
+        Opcode {
+            instruction,
+            mode,
+            bytes,
+            cycles,
+            documented: false,
+        }
+    }
+
+    /// The instruction's mnemonic.
+    pub fn mnemonic(&self) -> &'static str {
+        self.instruction.mnemonic()
+    }
+}
+
+/// The NMOS 6502 and 2A03 table, indexed by opcode byte.
+pub const NMOS_OPCODES: [Opcode; 256] = [
+    // 0x00
+    Opcode::new(Instruction::Brk, AddressingMode::Implicit, 1, 7),
+    Opcode::new(Instruction::Ora, AddressingMode::IndexedIndirect, 2, 6),
+    Opcode::undocumented(Instruction::Jam, AddressingMode::Implicit, 1, 2),
+    Opcode::undocumented(Instruction::Slo, AddressingMode::IndexedIndirect, 2, 8),
+    Opcode::undocumented(Instruction::Nop, AddressingMode::ZeroPage, 2, 3),
+    Opcode::new(Instruction::Ora, AddressingMode::ZeroPage, 2, 3),
+    Opcode::new(Instruction::Asl, AddressingMode::ZeroPage, 2, 5),
+    Opcode::undocumented(Instruction::Slo, AddressingMode::ZeroPage, 2, 5),
+    Opcode::new(Instruction::Php, AddressingMode::Implicit, 1, 3),
+    Opcode::new(Instruction::Ora, AddressingMode::Immediate, 2, 2),
+    Opcode::new(Instruction::Asl, AddressingMode::Accumulator, 1, 2),
+    Opcode::undocumented(Instruction::Anc, AddressingMode::Immediate, 2, 2),
+    Opcode::undocumented(Instruction::Nop, AddressingMode::Absolute, 3, 4),
+    Opcode::new(Instruction::Ora, AddressingMode::Absolute, 3, 4),
+    Opcode::new(Instruction::Asl, AddressingMode::Absolute, 3, 6),
+    Opcode::undocumented(Instruction::Slo, AddressingMode::Absolute, 3, 6),
+    // 0x10
+    Opcode::new(Instruction::Bpl, AddressingMode::Relative, 2, 2),
+    Opcode::new(Instruction::Ora, AddressingMode::IndirectIndexed, 2, 5),
+    Opcode::undocumented(Instruction::Jam, AddressingMode::Implicit, 1, 2),
+    Opcode::undocumented(Instruction::Slo, AddressingMode::IndirectIndexed, 2, 8),
+    Opcode::undocumented(Instruction::Nop, AddressingMode::ZeroPageX, 2, 4),
+    Opcode::new(Instruction::Ora, AddressingMode::ZeroPageX, 2, 4),
+    Opcode::new(Instruction::Asl, AddressingMode::ZeroPageX, 2, 6),
+    Opcode::undocumented(Instruction::Slo, AddressingMode::ZeroPageX, 2, 6),
+    Opcode::new(Instruction::Clc, AddressingMode::Implicit, 1, 2),
+    Opcode::new(Instruction::Ora, AddressingMode::AbsoluteY, 3, 4),
+    Opcode::undocumented(Instruction::Nop, AddressingMode::Implicit, 1, 2),
+    Opcode::undocumented(Instruction::Slo, AddressingMode::AbsoluteY, 3, 7),
+    Opcode::undocumented(Instruction::Nop, AddressingMode::AbsoluteX, 3, 4),
+    Opcode::new(Instruction::Ora, AddressingMode::AbsoluteX, 3, 4),
+    Opcode::new(Instruction::Asl, AddressingMode::AbsoluteX, 3, 7),
+    Opcode::undocumented(Instruction::Slo, AddressingMode::AbsoluteX, 3, 7),
+    // 0x20
+    Opcode::new(Instruction::Jsr, AddressingMode::Absolute, 3, 6),
+    Opcode::new(Instruction::And, AddressingMode::IndexedIndirect, 2, 6),
+    Opcode::undocumented(Instruction::Jam, AddressingMode::Implicit, 1, 2),
+    Opcode::undocumented(Instruction::Rla, AddressingMode::IndexedIndirect, 2, 8),
+    Opcode::new(Instruction::Bit, AddressingMode::ZeroPage, 2, 3),
+    Opcode::new(Instruction::And, AddressingMode::ZeroPage, 2, 3),
+    Opcode::new(Instruction::Rol, AddressingMode::ZeroPage, 2, 5),
+    Opcode::undocumented(Instruction::Rla, AddressingMode::ZeroPage, 2, 5),
+    Opcode::new(Instruction::Plp, AddressingMode::Implicit, 1, 4),
+    Opcode::new(Instruction::And, AddressingMode::Immediate, 2, 2),
+    Opcode::new(Instruction::Rol, AddressingMode::Accumulator, 1, 2),
+    Opcode::undocumented(Instruction::Anc, AddressingMode::Immediate, 2, 2),
+    Opcode::new(Instruction::Bit, AddressingMode::Absolute, 3, 4),
+    Opcode::new(Instruction::And, AddressingMode::Absolute, 3, 4),
+    Opcode::new(Instruction::Rol, AddressingMode::Absolute, 3, 6),
+    Opcode::undocumented(Instruction::Rla, AddressingMode::Absolute, 3, 6),
+    // 0x30
+    Opcode::new(Instruction::Bmi, AddressingMode::Relative, 2, 2),
+    Opcode::new(Instruction::And, AddressingMode::IndirectIndexed, 2, 5),
+    Opcode::undocumented(Instruction::Jam, AddressingMode::Implicit, 1, 2),
+    Opcode::undocumented(Instruction::Rla, AddressingMode::IndirectIndexed, 2, 8),
+    Opcode::undocumented(Instruction::Nop, AddressingMode::ZeroPageX, 2, 4),
+    Opcode::new(Instruction::And, AddressingMode::ZeroPageX, 2, 4),
+    Opcode::new(Instruction::Rol, AddressingMode::ZeroPageX, 2, 6),
+    Opcode::undocumented(Instruction::Rla, AddressingMode::ZeroPageX, 2, 6),
+    Opcode::new(Instruction::Sec, AddressingMode::Implicit, 1, 2),
+    Opcode::new(Instruction::And, AddressingMode::AbsoluteY, 3, 4),
+    Opcode::undocumented(Instruction::Nop, AddressingMode::Implicit, 1, 2),
+    Opcode::undocumented(Instruction::Rla, AddressingMode::AbsoluteY, 3, 7),
+    Opcode::undocumented(Instruction::Nop, AddressingMode::AbsoluteX, 3, 4),
+    Opcode::new(Instruction::And, AddressingMode::AbsoluteX, 3, 4),
+    Opcode::new(Instruction::Rol, AddressingMode::AbsoluteX, 3, 7),
+    Opcode::undocumented(Instruction::Rla, AddressingMode::AbsoluteX, 3, 7),
+    // 0x40
+    Opcode::new(Instruction::Rti, AddressingMode::Implicit, 1, 6),
+    Opcode::new(Instruction::Eor, AddressingMode::IndexedIndirect, 2, 6),
+    Opcode::undocumented(Instruction::Jam, AddressingMode::Implicit, 1, 2),
+    Opcode::undocumented(Instruction::Sre, AddressingMode::IndexedIndirect, 2, 8),
+    Opcode::undocumented(Instruction::Nop, AddressingMode::ZeroPage, 2, 3),
+    Opcode::new(Instruction::Eor, AddressingMode::ZeroPage, 2, 3),
+    Opcode::new(Instruction::Lsr, AddressingMode::ZeroPage, 2, 5),
+    Opcode::undocumented(Instruction::Sre, AddressingMode::ZeroPage, 2, 5),
+    Opcode::new(Instruction::Pha, AddressingMode::Implicit, 1, 3),
+    Opcode::new(Instruction::Eor, AddressingMode::Immediate, 2, 2),
+    Opcode::new(Instruction::Lsr, AddressingMode::Accumulator, 1, 2),
+    Opcode::undocumented(Instruction::Alr, AddressingMode::Immediate, 2, 2),
+    Opcode::new(Instruction::Jmp, AddressingMode::Absolute, 3, 3),
+    Opcode::new(Instruction::Eor, AddressingMode::Absolute, 3, 4),
+    Opcode::new(Instruction::Lsr, AddressingMode::Absolute, 3, 6),
+    Opcode::undocumented(Instruction::Sre, AddressingMode::Absolute, 3, 6),
+    // 0x50
+    Opcode::new(Instruction::Bvc, AddressingMode::Relative, 2, 2),
+    Opcode::new(Instruction::Eor, AddressingMode::IndirectIndexed, 2, 5),
+    Opcode::undocumented(Instruction::Jam, AddressingMode::Implicit, 1, 2),
+    Opcode::undocumented(Instruction::Sre, AddressingMode::IndirectIndexed, 2, 8),
+    Opcode::undocumented(Instruction::Nop, AddressingMode::ZeroPageX, 2, 4),
+    Opcode::new(Instruction::Eor, AddressingMode::ZeroPageX, 2, 4),
+    Opcode::new(Instruction::Lsr, AddressingMode::ZeroPageX, 2, 6),
+    Opcode::undocumented(Instruction::Sre, AddressingMode::ZeroPageX, 2, 6),
+    Opcode::new(Instruction::Cli, AddressingMode::Implicit, 1, 2),
+    Opcode::new(Instruction::Eor, AddressingMode::AbsoluteY, 3, 4),
+    Opcode::undocumented(Instruction::Nop, AddressingMode::Implicit, 1, 2),
+    Opcode::undocumented(Instruction::Sre, AddressingMode::AbsoluteY, 3, 7),
+    Opcode::undocumented(Instruction::Nop, AddressingMode::AbsoluteX, 3, 4),
+    Opcode::new(Instruction::Eor, AddressingMode::AbsoluteX, 3, 4),
+    Opcode::new(Instruction::Lsr, AddressingMode::AbsoluteX, 3, 7),
+    Opcode::undocumented(Instruction::Sre, AddressingMode::AbsoluteX, 3, 7),
+    // 0x60
+    Opcode::new(Instruction::Rts, AddressingMode::Implicit, 1, 6),
+    Opcode::new(Instruction::Adc, AddressingMode::IndexedIndirect, 2, 6),
+    Opcode::undocumented(Instruction::Jam, AddressingMode::Implicit, 1, 2),
+    Opcode::undocumented(Instruction::Rra, AddressingMode::IndexedIndirect, 2, 8),
+    Opcode::undocumented(Instruction::Nop, AddressingMode::ZeroPage, 2, 3),
+    Opcode::new(Instruction::Adc, AddressingMode::ZeroPage, 2, 3),
+    Opcode::new(Instruction::Ror, AddressingMode::ZeroPage, 2, 5),
+    Opcode::undocumented(Instruction::Rra, AddressingMode::ZeroPage, 2, 5),
+    Opcode::new(Instruction::Pla, AddressingMode::Implicit, 1, 4),
+    Opcode::new(Instruction::Adc, AddressingMode::Immediate, 2, 2),
+    Opcode::new(Instruction::Ror, AddressingMode::Accumulator, 1, 2),
+    Opcode::undocumented(Instruction::Arr, AddressingMode::Immediate, 2, 2),
+    Opcode::new(Instruction::Jmp, AddressingMode::Indirect, 3, 5),
+    Opcode::new(Instruction::Adc, AddressingMode::Absolute, 3, 4),
+    Opcode::new(Instruction::Ror, AddressingMode::Absolute, 3, 6),
+    Opcode::undocumented(Instruction::Rra, AddressingMode::Absolute, 3, 6),
+    // 0x70
+    Opcode::new(Instruction::Bvs, AddressingMode::Relative, 2, 2),
+    Opcode::new(Instruction::Adc, AddressingMode::IndirectIndexed, 2, 5),
+    Opcode::undocumented(Instruction::Jam, AddressingMode::Implicit, 1, 2),
+    Opcode::undocumented(Instruction::Rra, AddressingMode::IndirectIndexed, 2, 8),
+    Opcode::undocumented(Instruction::Nop, AddressingMode::ZeroPageX, 2, 4),
+    Opcode::new(Instruction::Adc, AddressingMode::ZeroPageX, 2, 4),
+    Opcode::new(Instruction::Ror, AddressingMode::ZeroPageX, 2, 6),
+    Opcode::undocumented(Instruction::Rra, AddressingMode::ZeroPageX, 2, 6),
+    Opcode::new(Instruction::Sei, AddressingMode::Implicit, 1, 2),
+    Opcode::new(Instruction::Adc, AddressingMode::AbsoluteY, 3, 4),
+    Opcode::undocumented(Instruction::Nop, AddressingMode::Implicit, 1, 2),
+    Opcode::undocumented(Instruction::Rra, AddressingMode::AbsoluteY, 3, 7),
+    Opcode::undocumented(Instruction::Nop, AddressingMode::AbsoluteX, 3, 4),
+    Opcode::new(Instruction::Adc, AddressingMode::AbsoluteX, 3, 4),
+    Opcode::new(Instruction::Ror, AddressingMode::AbsoluteX, 3, 7),
+    Opcode::undocumented(Instruction::Rra, AddressingMode::AbsoluteX, 3, 7),
+    // 0x80
+    Opcode::undocumented(Instruction::Nop, AddressingMode::Immediate, 2, 2),
+    Opcode::new(Instruction::Sta, AddressingMode::IndexedIndirect, 2, 6),
+    Opcode::undocumented(Instruction::Nop, AddressingMode::Immediate, 2, 2),
+    Opcode::undocumented(Instruction::Sax, AddressingMode::IndexedIndirect, 2, 6),
+    Opcode::new(Instruction::Sty, AddressingMode::ZeroPage, 2, 3),
+    Opcode::new(Instruction::Sta, AddressingMode::ZeroPage, 2, 3),
+    Opcode::new(Instruction::Stx, AddressingMode::ZeroPage, 2, 3),
+    Opcode::undocumented(Instruction::Sax, AddressingMode::ZeroPage, 2, 3),
+    Opcode::new(Instruction::Dey, AddressingMode::Implicit, 1, 2),
+    Opcode::undocumented(Instruction::Nop, AddressingMode::Immediate, 2, 2),
+    Opcode::new(Instruction::Txa, AddressingMode::Implicit, 1, 2),
+    Opcode::undocumented(Instruction::Xaa, AddressingMode::Immediate, 2, 2),
+    Opcode::new(Instruction::Sty, AddressingMode::Absolute, 3, 4),
+    Opcode::new(Instruction::Sta, AddressingMode::Absolute, 3, 4),
+    Opcode::new(Instruction::Stx, AddressingMode::Absolute, 3, 4),
+    Opcode::undocumented(Instruction::Sax, AddressingMode::Absolute, 3, 4),
+    // 0x90
+    Opcode::new(Instruction::Bcc, AddressingMode::Relative, 2, 2),
+    Opcode::new(Instruction::Sta, AddressingMode::IndirectIndexed, 2, 6),
+    Opcode::undocumented(Instruction::Jam, AddressingMode::Implicit, 1, 2),
+    Opcode::undocumented(Instruction::Sha, AddressingMode::IndirectIndexed, 2, 6),
+    Opcode::new(Instruction::Sty, AddressingMode::ZeroPageX, 2, 4),
+    Opcode::new(Instruction::Sta, AddressingMode::ZeroPageX, 2, 4),
+    Opcode::new(Instruction::Stx, AddressingMode::ZeroPageY, 2, 4),
+    Opcode::undocumented(Instruction::Sax, AddressingMode::ZeroPageY, 2, 4),
+    Opcode::new(Instruction::Tya, AddressingMode::Implicit, 1, 2),
+    Opcode::new(Instruction::Sta, AddressingMode::AbsoluteY, 3, 5),
+    Opcode::new(Instruction::Txs, AddressingMode::Implicit, 1, 2),
+    Opcode::undocumented(Instruction::Tas, AddressingMode::AbsoluteY, 3, 5),
+    Opcode::undocumented(Instruction::Shy, AddressingMode::AbsoluteX, 3, 5),
+    Opcode::new(Instruction::Sta, AddressingMode::AbsoluteX, 3, 5),
+    Opcode::undocumented(Instruction::Shx, AddressingMode::AbsoluteY, 3, 5),
+    Opcode::undocumented(Instruction::Sha, AddressingMode::AbsoluteY, 3, 5),
+    // 0xA0
+    Opcode::new(Instruction::Ldy, AddressingMode::Immediate, 2, 2),
+    Opcode::new(Instruction::Lda, AddressingMode::IndexedIndirect, 2, 6),
+    Opcode::new(Instruction::Ldx, AddressingMode::Immediate, 2, 2),
+    Opcode::undocumented(Instruction::Lax, AddressingMode::IndexedIndirect, 2, 6),
+    Opcode::new(Instruction::Ldy, AddressingMode::ZeroPage, 2, 3),
+    Opcode::new(Instruction::Lda, AddressingMode::ZeroPage, 2, 3),
+    Opcode::new(Instruction::Ldx, AddressingMode::ZeroPage, 2, 3),
+    Opcode::undocumented(Instruction::Lax, AddressingMode::ZeroPage, 2, 3),
+    Opcode::new(Instruction::Tay, AddressingMode::Implicit, 1, 2),
+    Opcode::new(Instruction::Lda, AddressingMode::Immediate, 2, 2),
+    Opcode::new(Instruction::Tax, AddressingMode::Implicit, 1, 2),
+    Opcode::undocumented(Instruction::Lxa, AddressingMode::Immediate, 2, 2),
+    Opcode::new(Instruction::Ldy, AddressingMode::Absolute, 3, 4),
+    Opcode::new(Instruction::Lda, AddressingMode::Absolute, 3, 4),
+    Opcode::new(Instruction::Ldx, AddressingMode::Absolute, 3, 4),
+    Opcode::undocumented(Instruction::Lax, AddressingMode::Absolute, 3, 4),
+    // 0xB0
+    Opcode::new(Instruction::Bcs, AddressingMode::Relative, 2, 2),
+    Opcode::new(Instruction::Lda, AddressingMode::IndirectIndexed, 2, 5),
+    Opcode::undocumented(Instruction::Jam, AddressingMode::Implicit, 1, 2),
+    Opcode::undocumented(Instruction::Lax, AddressingMode::IndirectIndexed, 2, 5),
+    Opcode::new(Instruction::Ldy, AddressingMode::ZeroPageX, 2, 4),
+    Opcode::new(Instruction::Lda, AddressingMode::ZeroPageX, 2, 4),
+    Opcode::new(Instruction::Ldx, AddressingMode::ZeroPageY, 2, 4),
+    Opcode::undocumented(Instruction::Lax, AddressingMode::ZeroPageY, 2, 4),
+    Opcode::new(Instruction::Clv, AddressingMode::Implicit, 1, 2),
+    Opcode::new(Instruction::Lda, AddressingMode::AbsoluteY, 3, 4),
+    Opcode::new(Instruction::Tsx, AddressingMode::Implicit, 1, 2),
+    Opcode::undocumented(Instruction::Las, AddressingMode::AbsoluteY, 3, 4),
+    Opcode::new(Instruction::Ldy, AddressingMode::AbsoluteX, 3, 4),
+    Opcode::new(Instruction::Lda, AddressingMode::AbsoluteX, 3, 4),
+    Opcode::new(Instruction::Ldx, AddressingMode::AbsoluteY, 3, 4),
+    Opcode::undocumented(Instruction::Lax, AddressingMode::AbsoluteY, 3, 4),
+    // 0xC0
+    Opcode::new(Instruction::Cpy, AddressingMode::Immediate, 2, 2),
+    Opcode::new(Instruction::Cmp, AddressingMode::IndexedIndirect, 2, 6),
+    Opcode::undocumented(Instruction::Nop, AddressingMode::Immediate, 2, 2),
+    Opcode::undocumented(Instruction::Dcp, AddressingMode::IndexedIndirect, 2, 8),
+    Opcode::new(Instruction::Cpy, AddressingMode::ZeroPage, 2, 3),
+    Opcode::new(Instruction::Cmp, AddressingMode::ZeroPage, 2, 3),
+    Opcode::new(Instruction::Dec, AddressingMode::ZeroPage, 2, 5),
+    Opcode::undocumented(Instruction::Dcp, AddressingMode::ZeroPage, 2, 5),
+    Opcode::new(Instruction::Iny, AddressingMode::Implicit, 1, 2),
+    Opcode::new(Instruction::Cmp, AddressingMode::Immediate, 2, 2),
+    Opcode::new(Instruction::Dex, AddressingMode::Implicit, 1, 2),
+    Opcode::undocumented(Instruction::Sbx, AddressingMode::Immediate, 2, 2),
+    Opcode::new(Instruction::Cpy, AddressingMode::Absolute, 3, 4),
+    Opcode::new(Instruction::Cmp, AddressingMode::Absolute, 3, 4),
+    Opcode::new(Instruction::Dec, AddressingMode::Absolute, 3, 6),
+    Opcode::undocumented(Instruction::Dcp, AddressingMode::Absolute, 3, 6),
+    // 0xD0
+    Opcode::new(Instruction::Bne, AddressingMode::Relative, 2, 2),
+    Opcode::new(Instruction::Cmp, AddressingMode::IndirectIndexed, 2, 5),
+    Opcode::undocumented(Instruction::Jam, AddressingMode::Implicit, 1, 2),
+    Opcode::undocumented(Instruction::Dcp, AddressingMode::IndirectIndexed, 2, 8),
+    Opcode::undocumented(Instruction::Nop, AddressingMode::ZeroPageX, 2, 4),
+    Opcode::new(Instruction::Cmp, AddressingMode::ZeroPageX, 2, 4),
+    Opcode::new(Instruction::Dec, AddressingMode::ZeroPageX, 2, 6),
+    Opcode::undocumented(Instruction::Dcp, AddressingMode::ZeroPageX, 2, 6),
+    Opcode::new(Instruction::Cld, AddressingMode::Implicit, 1, 2),
+    Opcode::new(Instruction::Cmp, AddressingMode::AbsoluteY, 3, 4),
+    Opcode::undocumented(Instruction::Nop, AddressingMode::Implicit, 1, 2),
+    Opcode::undocumented(Instruction::Dcp, AddressingMode::AbsoluteY, 3, 7),
+    Opcode::undocumented(Instruction::Nop, AddressingMode::AbsoluteX, 3, 4),
+    Opcode::new(Instruction::Cmp, AddressingMode::AbsoluteX, 3, 4),
+    Opcode::new(Instruction::Dec, AddressingMode::AbsoluteX, 3, 7),
+    Opcode::undocumented(Instruction::Dcp, AddressingMode::AbsoluteX, 3, 7),
+    // 0xE0
+    Opcode::new(Instruction::Cpx, AddressingMode::Immediate, 2, 2),
+    Opcode::new(Instruction::Sbc, AddressingMode::IndexedIndirect, 2, 6),
+    Opcode::undocumented(Instruction::Nop, AddressingMode::Immediate, 2, 2),
+    Opcode::undocumented(Instruction::Isc, AddressingMode::IndexedIndirect, 2, 8),
+    Opcode::new(Instruction::Cpx, AddressingMode::ZeroPage, 2, 3),
+    Opcode::new(Instruction::Sbc, AddressingMode::ZeroPage, 2, 3),
+    Opcode::new(Instruction::Inc, AddressingMode::ZeroPage, 2, 5),
+    Opcode::undocumented(Instruction::Isc, AddressingMode::ZeroPage, 2, 5),
+    Opcode::new(Instruction::Inx, AddressingMode::Implicit, 1, 2),
+    Opcode::new(Instruction::Sbc, AddressingMode::Immediate, 2, 2),
+    Opcode::new(Instruction::Nop, AddressingMode::Implicit, 1, 2),
+    Opcode::undocumented(Instruction::Sbc, AddressingMode::Immediate, 2, 2),
+    Opcode::new(Instruction::Cpx, AddressingMode::Absolute, 3, 4),
+    Opcode::new(Instruction::Sbc, AddressingMode::Absolute, 3, 4),
+    Opcode::new(Instruction::Inc, AddressingMode::Absolute, 3, 6),
+    Opcode::undocumented(Instruction::Isc, AddressingMode::Absolute, 3, 6),
+    // 0xF0
+    Opcode::new(Instruction::Beq, AddressingMode::Relative, 2, 2),
+    Opcode::new(Instruction::Sbc, AddressingMode::IndirectIndexed, 2, 5),
+    Opcode::undocumented(Instruction::Jam, AddressingMode::Implicit, 1, 2),
+    Opcode::undocumented(Instruction::Isc, AddressingMode::IndirectIndexed, 2, 8),
+    Opcode::undocumented(Instruction::Nop, AddressingMode::ZeroPageX, 2, 4),
+    Opcode::new(Instruction::Sbc, AddressingMode::ZeroPageX, 2, 4),
+    Opcode::new(Instruction::Inc, AddressingMode::ZeroPageX, 2, 6),
+    Opcode::undocumented(Instruction::Isc, AddressingMode::ZeroPageX, 2, 6),
+    Opcode::new(Instruction::Sed, AddressingMode::Implicit, 1, 2),
+    Opcode::new(Instruction::Sbc, AddressingMode::AbsoluteY, 3, 4),
+    Opcode::undocumented(Instruction::Nop, AddressingMode::Implicit, 1, 2),
+    Opcode::undocumented(Instruction::Isc, AddressingMode::AbsoluteY, 3, 7),
+    Opcode::undocumented(Instruction::Nop, AddressingMode::AbsoluteX, 3, 4),
+    Opcode::new(Instruction::Sbc, AddressingMode::AbsoluteX, 3, 4),
+    Opcode::new(Instruction::Inc, AddressingMode::AbsoluteX, 3, 7),
+    Opcode::undocumented(Instruction::Isc, AddressingMode::AbsoluteX, 3, 7),
+];
+
+/// The 65C02 table, indexed by opcode byte.
+pub const CMOS_OPCODES: [Opcode; 256] = [
+    // 0x00
+    Opcode::new(Instruction::Brk, AddressingMode::Implicit, 1, 7),
+    Opcode::new(Instruction::Ora, AddressingMode::IndexedIndirect, 2, 6),
+    Opcode::undocumented(Instruction::Nop, AddressingMode::Immediate, 2, 2),
+    Opcode::undocumented(Instruction::Nop, AddressingMode::Implicit, 1, 1),
+    Opcode::new(Instruction::Tsb, AddressingMode::ZeroPage, 2, 5),
+    Opcode::new(Instruction::Ora, AddressingMode::ZeroPage, 2, 3),
+    Opcode::new(Instruction::Asl, AddressingMode::ZeroPage, 2, 5),
+    Opcode::new(Instruction::Rmb(0), AddressingMode::ZeroPage, 2, 5),
+    Opcode::new(Instruction::Php, AddressingMode::Implicit, 1, 3),
+    Opcode::new(Instruction::Ora, AddressingMode::Immediate, 2, 2),
+    Opcode::new(Instruction::Asl, AddressingMode::Accumulator, 1, 2),
+    Opcode::undocumented(Instruction::Nop, AddressingMode::Implicit, 1, 1),
+    Opcode::new(Instruction::Tsb, AddressingMode::Absolute, 3, 6),
+    Opcode::new(Instruction::Ora, AddressingMode::Absolute, 3, 4),
+    Opcode::new(Instruction::Asl, AddressingMode::Absolute, 3, 6),
+    Opcode::new(Instruction::Bbr(0), AddressingMode::ZeroPageRelative, 3, 5),
+    // 0x10
+    Opcode::new(Instruction::Bpl, AddressingMode::Relative, 2, 2),
+    Opcode::new(Instruction::Ora, AddressingMode::IndirectIndexed, 2, 5),
+    Opcode::new(Instruction::Ora, AddressingMode::ZeroPageIndirect, 2, 5),
+    Opcode::undocumented(Instruction::Nop, AddressingMode::Implicit, 1, 1),
+    Opcode::new(Instruction::Trb, AddressingMode::ZeroPage, 2, 5),
+    Opcode::new(Instruction::Ora, AddressingMode::ZeroPageX, 2, 4),
+    Opcode::new(Instruction::Asl, AddressingMode::ZeroPageX, 2, 6),
+    Opcode::new(Instruction::Rmb(1), AddressingMode::ZeroPage, 2, 5),
+    Opcode::new(Instruction::Clc, AddressingMode::Implicit, 1, 2),
+    Opcode::new(Instruction::Ora, AddressingMode::AbsoluteY, 3, 4),
+    Opcode::new(Instruction::Inc, AddressingMode::Accumulator, 1, 2),
+    Opcode::undocumented(Instruction::Nop, AddressingMode::Implicit, 1, 1),
+    Opcode::new(Instruction::Trb, AddressingMode::Absolute, 3, 6),
+    Opcode::new(Instruction::Ora, AddressingMode::AbsoluteX, 3, 4),
+    Opcode::new(Instruction::Asl, AddressingMode::AbsoluteX, 3, 6),
+    Opcode::new(Instruction::Bbr(1), AddressingMode::ZeroPageRelative, 3, 5),
+    // 0x20
+    Opcode::new(Instruction::Jsr, AddressingMode::Absolute, 3, 6),
+    Opcode::new(Instruction::And, AddressingMode::IndexedIndirect, 2, 6),
+    Opcode::undocumented(Instruction::Nop, AddressingMode::Immediate, 2, 2),
+    Opcode::undocumented(Instruction::Nop, AddressingMode::Implicit, 1, 1),
+    Opcode::new(Instruction::Bit, AddressingMode::ZeroPage, 2, 3),
+    Opcode::new(Instruction::And, AddressingMode::ZeroPage, 2, 3),
+    Opcode::new(Instruction::Rol, AddressingMode::ZeroPage, 2, 5),
+    Opcode::new(Instruction::Rmb(2), AddressingMode::ZeroPage, 2, 5),
+    Opcode::new(Instruction::Plp, AddressingMode::Implicit, 1, 4),
+    Opcode::new(Instruction::And, AddressingMode::Immediate, 2, 2),
+    Opcode::new(Instruction::Rol, AddressingMode::Accumulator, 1, 2),
+    Opcode::undocumented(Instruction::Nop, AddressingMode::Implicit, 1, 1),
+    Opcode::new(Instruction::Bit, AddressingMode::Absolute, 3, 4),
+    Opcode::new(Instruction::And, AddressingMode::Absolute, 3, 4),
+    Opcode::new(Instruction::Rol, AddressingMode::Absolute, 3, 6),
+    Opcode::new(Instruction::Bbr(2), AddressingMode::ZeroPageRelative, 3, 5),
+    // 0x30
+    Opcode::new(Instruction::Bmi, AddressingMode::Relative, 2, 2),
+    Opcode::new(Instruction::And, AddressingMode::IndirectIndexed, 2, 5),
+    Opcode::new(Instruction::And, AddressingMode::ZeroPageIndirect, 2, 5),
+    Opcode::undocumented(Instruction::Nop, AddressingMode::Implicit, 1, 1),
+    Opcode::new(Instruction::Bit, AddressingMode::ZeroPageX, 2, 4),
+    Opcode::new(Instruction::And, AddressingMode::ZeroPageX, 2, 4),
+    Opcode::new(Instruction::Rol, AddressingMode::ZeroPageX, 2, 6),
+    Opcode::new(Instruction::Rmb(3), AddressingMode::ZeroPage, 2, 5),
+    Opcode::new(Instruction::Sec, AddressingMode::Implicit, 1, 2),
+    Opcode::new(Instruction::And, AddressingMode::AbsoluteY, 3, 4),
+    Opcode::new(Instruction::Dec, AddressingMode::Accumulator, 1, 2),
+    Opcode::undocumented(Instruction::Nop, AddressingMode::Implicit, 1, 1),
+    Opcode::new(Instruction::Bit, AddressingMode::AbsoluteX, 3, 4),
+    Opcode::new(Instruction::And, AddressingMode::AbsoluteX, 3, 4),
+    Opcode::new(Instruction::Rol, AddressingMode::AbsoluteX, 3, 6),
+    Opcode::new(Instruction::Bbr(3), AddressingMode::ZeroPageRelative, 3, 5),
+    // 0x40
+    Opcode::new(Instruction::Rti, AddressingMode::Implicit, 1, 6),
+    Opcode::new(Instruction::Eor, AddressingMode::IndexedIndirect, 2, 6),
+    Opcode::undocumented(Instruction::Nop, AddressingMode::Immediate, 2, 2),
+    Opcode::undocumented(Instruction::Nop, AddressingMode::Implicit, 1, 1),
+    Opcode::undocumented(Instruction::Nop, AddressingMode::ZeroPage, 2, 3),
+    Opcode::new(Instruction::Eor, AddressingMode::ZeroPage, 2, 3),
+    Opcode::new(Instruction::Lsr, AddressingMode::ZeroPage, 2, 5),
+    Opcode::new(Instruction::Rmb(4), AddressingMode::ZeroPage, 2, 5),
+    Opcode::new(Instruction::Pha, AddressingMode::Implicit, 1, 3),
+    Opcode::new(Instruction::Eor, AddressingMode::Immediate, 2, 2),
+    Opcode::new(Instruction::Lsr, AddressingMode::Accumulator, 1, 2),
+    Opcode::undocumented(Instruction::Nop, AddressingMode::Implicit, 1, 1),
+    Opcode::new(Instruction::Jmp, AddressingMode::Absolute, 3, 3),
+    Opcode::new(Instruction::Eor, AddressingMode::Absolute, 3, 4),
+    Opcode::new(Instruction::Lsr, AddressingMode::Absolute, 3, 6),
+    Opcode::new(Instruction::Bbr(4), AddressingMode::ZeroPageRelative, 3, 5),
+    // 0x50
+    Opcode::new(Instruction::Bvc, AddressingMode::Relative, 2, 2),
+    Opcode::new(Instruction::Eor, AddressingMode::IndirectIndexed, 2, 5),
+    Opcode::new(Instruction::Eor, AddressingMode::ZeroPageIndirect, 2, 5),
+    Opcode::undocumented(Instruction::Nop, AddressingMode::Implicit, 1, 1),
+    Opcode::undocumented(Instruction::Nop, AddressingMode::ZeroPageX, 2, 4),
+    Opcode::new(Instruction::Eor, AddressingMode::ZeroPageX, 2, 4),
+    Opcode::new(Instruction::Lsr, AddressingMode::ZeroPageX, 2, 6),
+    Opcode::new(Instruction::Rmb(5), AddressingMode::ZeroPage, 2, 5),
+    Opcode::new(Instruction::Cli, AddressingMode::Implicit, 1, 2),
+    Opcode::new(Instruction::Eor, AddressingMode::AbsoluteY, 3, 4),
+    Opcode::new(Instruction::Phy, AddressingMode::Implicit, 1, 3),
+    Opcode::undocumented(Instruction::Nop, AddressingMode::Implicit, 1, 1),
+    Opcode::undocumented(Instruction::Nop, AddressingMode::Absolute, 3, 8),
+    Opcode::new(Instruction::Eor, AddressingMode::AbsoluteX, 3, 4),
+    Opcode::new(Instruction::Lsr, AddressingMode::AbsoluteX, 3, 6),
+    Opcode::new(Instruction::Bbr(5), AddressingMode::ZeroPageRelative, 3, 5),
+    // 0x60
+    Opcode::new(Instruction::Rts, AddressingMode::Implicit, 1, 6),
+    Opcode::new(Instruction::Adc, AddressingMode::IndexedIndirect, 2, 6),
+    Opcode::undocumented(Instruction::Nop, AddressingMode::Immediate, 2, 2),
+    Opcode::undocumented(Instruction::Nop, AddressingMode::Implicit, 1, 1),
+    Opcode::new(Instruction::Stz, AddressingMode::ZeroPage, 2, 3),
+    Opcode::new(Instruction::Adc, AddressingMode::ZeroPage, 2, 3),
+    Opcode::new(Instruction::Ror, AddressingMode::ZeroPage, 2, 5),
+    Opcode::new(Instruction::Rmb(6), AddressingMode::ZeroPage, 2, 5),
+    Opcode::new(Instruction::Pla, AddressingMode::Implicit, 1, 4),
+    Opcode::new(Instruction::Adc, AddressingMode::Immediate, 2, 2),
+    Opcode::new(Instruction::Ror, AddressingMode::Accumulator, 1, 2),
+    Opcode::undocumented(Instruction::Nop, AddressingMode::Implicit, 1, 1),
+    Opcode::new(Instruction::Jmp, AddressingMode::Indirect, 3, 6),
+    Opcode::new(Instruction::Adc, AddressingMode::Absolute, 3, 4),
+    Opcode::new(Instruction::Ror, AddressingMode::Absolute, 3, 6),
+    Opcode::new(Instruction::Bbr(6), AddressingMode::ZeroPageRelative, 3, 5),
+    // 0x70
+    Opcode::new(Instruction::Bvs, AddressingMode::Relative, 2, 2),
+    Opcode::new(Instruction::Adc, AddressingMode::IndirectIndexed, 2, 5),
+    Opcode::new(Instruction::Adc, AddressingMode::ZeroPageIndirect, 2, 5),
+    Opcode::undocumented(Instruction::Nop, AddressingMode::Implicit, 1, 1),
+    Opcode::new(Instruction::Stz, AddressingMode::ZeroPageX, 2, 4),
+    Opcode::new(Instruction::Adc, AddressingMode::ZeroPageX, 2, 4),
+    Opcode::new(Instruction::Ror, AddressingMode::ZeroPageX, 2, 6),
+    Opcode::new(Instruction::Rmb(7), AddressingMode::ZeroPage, 2, 5),
+    Opcode::new(Instruction::Sei, AddressingMode::Implicit, 1, 2),
+    Opcode::new(Instruction::Adc, AddressingMode::AbsoluteY, 3, 4),
+    Opcode::new(Instruction::Ply, AddressingMode::Implicit, 1, 4),
+    Opcode::undocumented(Instruction::Nop, AddressingMode::Implicit, 1, 1),
+    Opcode::new(
+        Instruction::Jmp,
+        AddressingMode::AbsoluteIndexedIndirect,
+        3,
+        6,
+    ),
+    Opcode::new(Instruction::Adc, AddressingMode::AbsoluteX, 3, 4),
+    Opcode::new(Instruction::Ror, AddressingMode::AbsoluteX, 3, 6),
+    Opcode::new(Instruction::Bbr(7), AddressingMode::ZeroPageRelative, 3, 5),
+    // 0x80
+    Opcode::new(Instruction::Bra, AddressingMode::Relative, 2, 2),
+    Opcode::new(Instruction::Sta, AddressingMode::IndexedIndirect, 2, 6),
+    Opcode::undocumented(Instruction::Nop, AddressingMode::Immediate, 2, 2),
+    Opcode::undocumented(Instruction::Nop, AddressingMode::Implicit, 1, 1),
+    Opcode::new(Instruction::Sty, AddressingMode::ZeroPage, 2, 3),
+    Opcode::new(Instruction::Sta, AddressingMode::ZeroPage, 2, 3),
+    Opcode::new(Instruction::Stx, AddressingMode::ZeroPage, 2, 3),
+    Opcode::new(Instruction::Smb(0), AddressingMode::ZeroPage, 2, 5),
+    Opcode::new(Instruction::Dey, AddressingMode::Implicit, 1, 2),
+    Opcode::new(Instruction::Bit, AddressingMode::Immediate, 2, 2),
+    Opcode::new(Instruction::Txa, AddressingMode::Implicit, 1, 2),
+    Opcode::undocumented(Instruction::Nop, AddressingMode::Implicit, 1, 1),
+    Opcode::new(Instruction::Sty, AddressingMode::Absolute, 3, 4),
+    Opcode::new(Instruction::Sta, AddressingMode::Absolute, 3, 4),
+    Opcode::new(Instruction::Stx, AddressingMode::Absolute, 3, 4),
+    Opcode::new(Instruction::Bbs(0), AddressingMode::ZeroPageRelative, 3, 5),
+    // 0x90
+    Opcode::new(Instruction::Bcc, AddressingMode::Relative, 2, 2),
+    Opcode::new(Instruction::Sta, AddressingMode::IndirectIndexed, 2, 6),
+    Opcode::new(Instruction::Sta, AddressingMode::ZeroPageIndirect, 2, 5),
+    Opcode::undocumented(Instruction::Nop, AddressingMode::Implicit, 1, 1),
+    Opcode::new(Instruction::Sty, AddressingMode::ZeroPageX, 2, 4),
+    Opcode::new(Instruction::Sta, AddressingMode::ZeroPageX, 2, 4),
+    Opcode::new(Instruction::Stx, AddressingMode::ZeroPageY, 2, 4),
+    Opcode::new(Instruction::Smb(1), AddressingMode::ZeroPage, 2, 5),
+    Opcode::new(Instruction::Tya, AddressingMode::Implicit, 1, 2),
+    Opcode::new(Instruction::Sta, AddressingMode::AbsoluteY, 3, 5),
+    Opcode::new(Instruction::Txs, AddressingMode::Implicit, 1, 2),
+    Opcode::undocumented(Instruction::Nop, AddressingMode::Implicit, 1, 1),
+    Opcode::new(Instruction::Stz, AddressingMode::Absolute, 3, 4),
+    Opcode::new(Instruction::Sta, AddressingMode::AbsoluteX, 3, 5),
+    Opcode::new(Instruction::Stz, AddressingMode::AbsoluteX, 3, 5),
+    Opcode::new(Instruction::Bbs(1), AddressingMode::ZeroPageRelative, 3, 5),
+    // 0xA0
+    Opcode::new(Instruction::Ldy, AddressingMode::Immediate, 2, 2),
+    Opcode::new(Instruction::Lda, AddressingMode::IndexedIndirect, 2, 6),
+    Opcode::new(Instruction::Ldx, AddressingMode::Immediate, 2, 2),
+    Opcode::undocumented(Instruction::Nop, AddressingMode::Implicit, 1, 1),
+    Opcode::new(Instruction::Ldy, AddressingMode::ZeroPage, 2, 3),
+    Opcode::new(Instruction::Lda, AddressingMode::ZeroPage, 2, 3),
+    Opcode::new(Instruction::Ldx, AddressingMode::ZeroPage, 2, 3),
+    Opcode::new(Instruction::Smb(2), AddressingMode::ZeroPage, 2, 5),
+    Opcode::new(Instruction::Tay, AddressingMode::Implicit, 1, 2),
+    Opcode::new(Instruction::Lda, AddressingMode::Immediate, 2, 2),
+    Opcode::new(Instruction::Tax, AddressingMode::Implicit, 1, 2),
+    Opcode::undocumented(Instruction::Nop, AddressingMode::Implicit, 1, 1),
+    Opcode::new(Instruction::Ldy, AddressingMode::Absolute, 3, 4),
+    Opcode::new(Instruction::Lda, AddressingMode::Absolute, 3, 4),
+    Opcode::new(Instruction::Ldx, AddressingMode::Absolute, 3, 4),
+    Opcode::new(Instruction::Bbs(2), AddressingMode::ZeroPageRelative, 3, 5),
+    // 0xB0
+    Opcode::new(Instruction::Bcs, AddressingMode::Relative, 2, 2),
+    Opcode::new(Instruction::Lda, AddressingMode::IndirectIndexed, 2, 5),
+    Opcode::new(Instruction::Lda, AddressingMode::ZeroPageIndirect, 2, 5),
+    Opcode::undocumented(Instruction::Nop, AddressingMode::Implicit, 1, 1),
+    Opcode::new(Instruction::Ldy, AddressingMode::ZeroPageX, 2, 4),
+    Opcode::new(Instruction::Lda, AddressingMode::ZeroPageX, 2, 4),
+    Opcode::new(Instruction::Ldx, AddressingMode::ZeroPageY, 2, 4),
+    Opcode::new(Instruction::Smb(3), AddressingMode::ZeroPage, 2, 5),
+    Opcode::new(Instruction::Clv, AddressingMode::Implicit, 1, 2),
+    Opcode::new(Instruction::Lda, AddressingMode::AbsoluteY, 3, 4),
+    Opcode::new(Instruction::Tsx, AddressingMode::Implicit, 1, 2),
+    Opcode::undocumented(Instruction::Nop, AddressingMode::Implicit, 1, 1),
+    Opcode::new(Instruction::Ldy, AddressingMode::AbsoluteX, 3, 4),
+    Opcode::new(Instruction::Lda, AddressingMode::AbsoluteX, 3, 4),
+    Opcode::new(Instruction::Ldx, AddressingMode::AbsoluteY, 3, 4),
+    Opcode::new(Instruction::Bbs(3), AddressingMode::ZeroPageRelative, 3, 5),
+    // 0xC0
+    Opcode::new(Instruction::Cpy, AddressingMode::Immediate, 2, 2),
+    Opcode::new(Instruction::Cmp, AddressingMode::IndexedIndirect, 2, 6),
+    Opcode::undocumented(Instruction::Nop, AddressingMode::Immediate, 2, 2),
+    Opcode::undocumented(Instruction::Nop, AddressingMode::Implicit, 1, 1),
+    Opcode::new(Instruction::Cpy, AddressingMode::ZeroPage, 2, 3),
+    Opcode::new(Instruction::Cmp, AddressingMode::ZeroPage, 2, 3),
+    Opcode::new(Instruction::Dec, AddressingMode::ZeroPage, 2, 5),
+    Opcode::new(Instruction::Smb(4), AddressingMode::ZeroPage, 2, 5),
+    Opcode::new(Instruction::Iny, AddressingMode::Implicit, 1, 2),
+    Opcode::new(Instruction::Cmp, AddressingMode::Immediate, 2, 2),
+    Opcode::new(Instruction::Dex, AddressingMode::Implicit, 1, 2),
+    Opcode::new(Instruction::Wai, AddressingMode::Implicit, 1, 3),
+    Opcode::new(Instruction::Cpy, AddressingMode::Absolute, 3, 4),
+    Opcode::new(Instruction::Cmp, AddressingMode::Absolute, 3, 4),
+    Opcode::new(Instruction::Dec, AddressingMode::Absolute, 3, 6),
+    Opcode::new(Instruction::Bbs(4), AddressingMode::ZeroPageRelative, 3, 5),
+    // 0xD0
+    Opcode::new(Instruction::Bne, AddressingMode::Relative, 2, 2),
+    Opcode::new(Instruction::Cmp, AddressingMode::IndirectIndexed, 2, 5),
+    Opcode::new(Instruction::Cmp, AddressingMode::ZeroPageIndirect, 2, 5),
+    Opcode::undocumented(Instruction::Nop, AddressingMode::Implicit, 1, 1),
+    Opcode::undocumented(Instruction::Nop, AddressingMode::ZeroPageX, 2, 4),
+    Opcode::new(Instruction::Cmp, AddressingMode::ZeroPageX, 2, 4),
+    Opcode::new(Instruction::Dec, AddressingMode::ZeroPageX, 2, 6),
+    Opcode::new(Instruction::Smb(5), AddressingMode::ZeroPage, 2, 5),
+    Opcode::new(Instruction::Cld, AddressingMode::Implicit, 1, 2),
+    Opcode::new(Instruction::Cmp, AddressingMode::AbsoluteY, 3, 4),
+    Opcode::new(Instruction::Phx, AddressingMode::Implicit, 1, 3),
+    Opcode::new(Instruction::Stp, AddressingMode::Implicit, 1, 3),
+    Opcode::undocumented(Instruction::Nop, AddressingMode::Absolute, 3, 4),
+    Opcode::new(Instruction::Cmp, AddressingMode::AbsoluteX, 3, 4),
+    Opcode::new(Instruction::Dec, AddressingMode::AbsoluteX, 3, 7),
+    Opcode::new(Instruction::Bbs(5), AddressingMode::ZeroPageRelative, 3, 5),
+    // 0xE0
+    Opcode::new(Instruction::Cpx, AddressingMode::Immediate, 2, 2),
+    Opcode::new(Instruction::Sbc, AddressingMode::IndexedIndirect, 2, 6),
+    Opcode::undocumented(Instruction::Nop, AddressingMode::Immediate, 2, 2),
+    Opcode::undocumented(Instruction::Nop, AddressingMode::Implicit, 1, 1),
+    Opcode::new(Instruction::Cpx, AddressingMode::ZeroPage, 2, 3),
+    Opcode::new(Instruction::Sbc, AddressingMode::ZeroPage, 2, 3),
+    Opcode::new(Instruction::Inc, AddressingMode::ZeroPage, 2, 5),
+    Opcode::new(Instruction::Smb(6), AddressingMode::ZeroPage, 2, 5),
+    Opcode::new(Instruction::Inx, AddressingMode::Implicit, 1, 2),
+    Opcode::new(Instruction::Sbc, AddressingMode::Immediate, 2, 2),
+    Opcode::new(Instruction::Nop, AddressingMode::Implicit, 1, 2),
+    Opcode::undocumented(Instruction::Nop, AddressingMode::Implicit, 1, 1),
+    Opcode::new(Instruction::Cpx, AddressingMode::Absolute, 3, 4),
+    Opcode::new(Instruction::Sbc, AddressingMode::Absolute, 3, 4),
+    Opcode::new(Instruction::Inc, AddressingMode::Absolute, 3, 6),
+    Opcode::new(Instruction::Bbs(6), AddressingMode::ZeroPageRelative, 3, 5),
+    // 0xF0
+    Opcode::new(Instruction::Beq, AddressingMode::Relative, 2, 2),
+    Opcode::new(Instruction::Sbc, AddressingMode::IndirectIndexed, 2, 5),
+    Opcode::new(Instruction::Sbc, AddressingMode::ZeroPageIndirect, 2, 5),
+    Opcode::undocumented(Instruction::Nop, AddressingMode::Implicit, 1, 1),
+    Opcode::undocumented(Instruction::Nop, AddressingMode::ZeroPageX, 2, 4),
+    Opcode::new(Instruction::Sbc, AddressingMode::ZeroPageX, 2, 4),
+    Opcode::new(Instruction::Inc, AddressingMode::ZeroPageX, 2, 6),
+    Opcode::new(Instruction::Smb(7), AddressingMode::ZeroPage, 2, 5),
+    Opcode::new(Instruction::Sed, AddressingMode::Implicit, 1, 2),
+    Opcode::new(Instruction::Sbc, AddressingMode::AbsoluteY, 3, 4),
+    Opcode::new(Instruction::Plx, AddressingMode::Implicit, 1, 4),
+    Opcode::undocumented(Instruction::Nop, AddressingMode::Implicit, 1, 1),
+    Opcode::undocumented(Instruction::Nop, AddressingMode::Absolute, 3, 4),
+    Opcode::new(Instruction::Sbc, AddressingMode::AbsoluteX, 3, 4),
+    Opcode::new(Instruction::Inc, AddressingMode::AbsoluteX, 3, 7),
+    Opcode::new(Instruction::Bbs(7), AddressingMode::ZeroPageRelative, 3, 5),
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bit_mnemonics_use_the_low_three_bits() {
+        assert_eq!(Instruction::Bbr(3).mnemonic(), "BBR3");
+        assert_eq!(Instruction::Bbs(8).mnemonic(), "BBS0");
+        assert_eq!(Instruction::Rmb(0xFF).mnemonic(), "RMB7");
+        assert_eq!(Instruction::Smb(13).mnemonic(), "SMB5");
+    }
+}
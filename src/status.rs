@@ -0,0 +1,134 @@
+//! The processor status register.
+
+const CARRY: u8 = 0b0000_0001;
+const ZERO: u8 = 0b0000_0010;
+const INTERRUPT_DISABLE: u8 = 0b0000_0100;
+const DECIMAL: u8 = 0b0000_1000;
+const BREAK: u8 = 0b0001_0000;
+const UNUSED: u8 = 0b0010_0000;
+const OVERFLOW: u8 = 0b0100_0000;
+const NEGATIVE: u8 = 0b1000_0000;
+
+/// The P register, NV-BDIZC from bit 7 down to bit 0.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct StatusFlags(u8);
+
+impl StatusFlags {
+    /// Wraps a raw register value.
+    pub fn from_bits(bits: u8) -> Self {
+        StatusFlags(bits)
+    }
+
+    /// The raw register value.
+    pub fn bits(self) -> u8 {
+        self.0
+    }
+
+    /// The byte pushed to the stack. B only exists there: PHP and BRK push it
+    /// set, IRQ and NMI push it clear, and the unused bit is always set.
+    pub fn to_pushed(self, break_flag: bool) -> u8 {
+        if break_flag {
+            self.0 | BREAK | UNUSED
+        } else {
+            (self.0 & !BREAK) | UNUSED
+        }
+    }
+
+    /// The register loaded by PLP and RTI, which drop B and force the unused
+    /// bit.
+    pub fn from_pulled(value: u8) -> Self {
+        StatusFlags((value & !BREAK) | UNUSED)
+    }
+
+    /// Whether the carry (C) flag is set.
+    pub fn carry(self) -> bool {
+        self.get(CARRY)
+    }
+
+    /// Sets or clears the carry (C) flag.
+    pub fn set_carry(&mut self, value: bool) {
+        self.set(CARRY, value);
+    }
+
+    /// Whether the zero (Z) flag is set.
+    pub fn zero(self) -> bool {
+        self.get(ZERO)
+    }
+
+    /// Sets or clears the zero (Z) flag.
+    pub fn set_zero(&mut self, value: bool) {
+        self.set(ZERO, value);
+    }
+
+    /// Whether the interrupt disable (I) flag is set.
+    pub fn interrupt_disable(self) -> bool {
+        self.get(INTERRUPT_DISABLE)
+    }
+
+    /// Sets or clears the interrupt disable (I) flag.
+    pub fn set_interrupt_disable(&mut self, value: bool) {
+        self.set(INTERRUPT_DISABLE, value);
+    }
+
+    /// Whether the decimal mode (D) flag is set.
+    pub fn decimal(self) -> bool {
+        self.get(DECIMAL)
+    }
+
+    /// Sets or clears the decimal mode (D) flag.
+    pub fn set_decimal(&mut self, value: bool) {
+        self.set(DECIMAL, value);
+    }
+
+    /// Whether the break (B) flag is set.
+    pub fn break_command(self) -> bool {
+        self.get(BREAK)
+    }
+
+    /// Sets or clears the break (B) flag.
+    pub fn set_break_command(&mut self, value: bool) {
+        self.set(BREAK, value);
+    }
+
+    /// Whether the unused bit 5 is set.
+    pub fn unused(self) -> bool {
+        self.get(UNUSED)
+    }
+
+    /// Sets or clears the unused bit 5.
+    pub fn set_unused(&mut self, value: bool) {
+        self.set(UNUSED, value);
+    }
+
+    /// Whether the overflow (V) flag is set.
+    pub fn overflow(self) -> bool {
+        self.get(OVERFLOW)
+    }
+
+    /// Sets or clears the overflow (V) flag.
+    pub fn set_overflow(&mut self, value: bool) {
+        self.set(OVERFLOW, value);
+    }
+
+    /// Whether the negative (N) flag is set.
+    pub fn negative(self) -> bool {
+        self.get(NEGATIVE)
+    }
+
+    /// Sets or clears the negative (N) flag.
+    pub fn set_negative(&mut self, value: bool) {
+        self.set(NEGATIVE, value);
+    }
+
+    fn get(self, mask: u8) -> bool {
+        self.0 & mask != 0
+    }
+
+    fn set(&mut self, mask: u8, value: bool) {
+        if value {
+            self.0 |= mask;
+        } else {
+            self.0 &= !mask;
+        }
+    }
+}
@@ -0,0 +1,552 @@
+//! Instruction tracing in the log formats of common emulators.
+
+use crate::bus::Bus;
+use crate::cpu::{Cpu, CpuState};
+use crate::disasm::{self, DecodedInstruction, Operand};
+use crate::opcode::{AddressingMode, Instruction, Opcode};
+use std::collections::VecDeque;
+use std::io::{self, Write};
+use std::ops::RangeInclusive;
+
+const DOTS_PER_SCANLINE: u64 = 341;
+const SCANLINES_PER_FRAME: u64 = 262;
+
+/// The layout of each trace line.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TraceFormat {
+    /// Nintendulator's nestest.log.
+    Nestest,
+    /// FCEUX's trace logger.
+    Fceux,
+    /// Mesen's trace logger.
+    Mesen,
+}
+
+/// Limits which instructions are traced. A tracer records an instruction
+/// only if every one of its filters accepts it.
+pub enum TraceFilter {
+    /// Keeps instructions whose PC is in the range.
+    PcRange(RangeInclusive<u16>),
+    /// Keeps instructions whose PC `bank_of` maps to `bank`; the CPU has no
+    /// notion of banking, so the caller supplies the mapping.
+    Bank {
+        /// The bank to keep.
+        bank: usize,
+        /// Maps a CPU address to its current bank.
+        bank_of: Box<dyn Fn(u16) -> usize + Send>,
+    },
+    /// Keeps instructions for which the condition holds on the registers
+    /// before they execute.
+    Condition(Box<dyn Fn(&CpuState) -> bool + Send>),
+}
+
+impl TraceFilter {
+    fn accepts(&self, state: &CpuState) -> bool {
+        match self {
+            TraceFilter::PcRange(range) => range.contains(&state.program_counter),
+            TraceFilter::Bank { bank, bank_of } => bank_of(state.program_counter) == *bank,
+            TraceFilter::Condition(condition) => condition(state),
+        }
+    }
+}
+
+enum Sink {
+    Writer(Box<dyn Write + Send>),
+    Ring {
+        lines: VecDeque<String>,
+        capacity: usize,
+    },
+}
+
+/// Records one line per instruction that passes every filter, either to a
+/// writer or into a ring buffer holding the most recent lines.
+pub struct Tracer {
+    format: TraceFormat,
+    filters: Vec<TraceFilter>,
+    sink: Sink,
+    error: Option<io::Error>,
+}
+
+impl Tracer {
+    /// A tracer writing each line to `writer`.
+    pub fn new<W: Write + Send + 'static>(format: TraceFormat, writer: W) -> Self {
+        Tracer::with_sink(format, Sink::Writer(Box::new(writer)))
+    }
+
+    /// A tracer keeping the last `capacity` lines in memory.
+    pub fn ring_buffer(format: TraceFormat, capacity: usize) -> Self {
+        Tracer::with_sink(
+            format,
+            Sink::Ring {
+                lines: VecDeque::with_capacity(capacity),
+                capacity,
+            },
+        )
+    }
+
+    fn with_sink(format: TraceFormat, sink: Sink) -> Self {
+        Tracer {
+            format,
+            filters: Vec::new(),
+            sink,
+            error: None,
+        }
+    }
+
+    /// Adds a filter.
+    pub fn with_filter(mut self, filter: TraceFilter) -> Self {
+        self.filters.push(filter);
+        self
+    }
+
+    /// The buffered lines, oldest first. Empty when writing to a writer.
+    pub fn lines(&self) -> impl Iterator<Item = &str> {
+        let lines = match &self.sink {
+            Sink::Ring { lines, .. } => Some(lines.iter().map(String::as_str)),
+            Sink::Writer(_) => None,
+        };
+        lines.into_iter().flatten()
+    }
+
+    /// Writes the buffered lines, oldest first.
+    pub fn write_lines<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        for line in self.lines() {
+            writeln!(writer, "{}", line)?;
+        }
+        Ok(())
+    }
+
+    /// Returns the first write error since the last call. Tracing stops
+    /// writing once an error has occurred.
+    pub fn take_error(&mut self) -> Option<io::Error> {
+        self.error.take()
+    }
+
+    /// Records the instruction at PC, if the filters accept it. The CPU calls
+    /// this before each instruction while the tracer is installed.
+    pub fn trace<B: Bus>(&mut self, cpu: &mut Cpu<B>) {
+        let state = cpu.state();
+        if !self.filters.iter().all(|filter| filter.accepts(&state)) {
+            return;
+        }
+
+        let line = format_line(cpu, self.format);
+        match &mut self.sink {
+            Sink::Writer(writer) => {
+                if self.error.is_none() {
+                    if let Err(error) = writeln!(writer, "{}", line) {
+                        self.error = Some(error);
+                    }
+                }
+            }
+            Sink::Ring { lines, capacity } => {
+                if *capacity == 0 {
+                    return;
+                }
+                if lines.len() == *capacity {
+                    lines.pop_front();
+                }
+                lines.push_back(line);
+            }
+        }
+    }
+}
+
+/// Formats the instruction at PC with the registers as they are before it
+/// executes. Memory is only looked at through `Bus::peek`.
+pub fn format_line<B: Bus>(cpu: &mut Cpu<B>, format: TraceFormat) -> String {
+    match format {
+        TraceFormat::Nestest => nestest_line(cpu),
+        TraceFormat::Fceux => fceux_line(cpu),
+        TraceFormat::Mesen => mesen_line(cpu),
+    }
+}
+
+/// Formats the instruction at PC in the layout of Nintendulator's
+/// nestest.log.
+pub fn nestest_line<B: Bus>(cpu: &mut Cpu<B>) -> String {
+    let (pc, opcode, bytes) = peek_instruction(cpu);
+
+    let mnemonic = match opcode.instruction {
+        Instruction::Isc => "ISB",
+        _ => opcode.mnemonic(),
+    };
+    let marker = if opcode.documented { ' ' } else { '*' };
+    let operand = nestest_operand(cpu, &opcode.instruction, &opcode.mode, pc, &bytes);
+    let disassembly = if operand.is_empty() {
+        mnemonic.to_string()
+    } else {
+        format!("{} {}", mnemonic, operand)
+    };
+
+    let dots = cpu.cycles() * 3 % (DOTS_PER_SCANLINE * SCANLINES_PER_FRAME);
+
+    format!(
+        "{:04X}  {:<8} {}{:<32}A:{:02X} X:{:02X} Y:{:02X} P:{:02X} SP:{:02X} PPU:{:>3},{:>3} CYC:{}",
+        pc,
+        hex_bytes(&bytes[..opcode.bytes as usize]),
+        marker,
+        disassembly,
+        cpu.accumulator(),
+        cpu.index_x(),
+        cpu.index_y(),
+        cpu.status().bits(),
+        cpu.stack_pointer(),
+        dots / DOTS_PER_SCANLINE,
+        dots % DOTS_PER_SCANLINE,
+        cpu.cycles()
+    )
+}
+
+// A bus has a byte at every address, so decoding from one is never cut off.
+fn decode_at_pc<B: Bus>(cpu: &mut Cpu<B>) -> DecodedInstruction {
+    let pc = cpu.program_counter();
+    let variant = cpu.variant();
+    disasm::disassemble_variant(cpu.bus_mut(), pc, variant).expect("bus source ran out")
+}
+
+fn fceux_line<B: Bus>(cpu: &mut Cpu<B>) -> String {
+    let pc = cpu.program_counter();
+    let instruction = decode_at_pc(cpu);
+
+    format!(
+        "c{:<11}A:{:02X} X:{:02X} Y:{:02X} S:{:02X} P:{}  ${:04X}: {:<8}  {}{}",
+        cpu.cycles(),
+        cpu.accumulator(),
+        cpu.index_x(),
+        cpu.index_y(),
+        cpu.stack_pointer(),
+        flag_letters(cpu.status().bits()),
+        pc,
+        hex_bytes(instruction.bytes()),
+        instruction,
+        annotation(cpu, &instruction, TraceFormat::Fceux)
+    )
+}
+
+fn mesen_line<B: Bus>(cpu: &mut Cpu<B>) -> String {
+    let pc = cpu.program_counter();
+    let instruction = decode_at_pc(cpu);
+    let disassembly = format!(
+        "{}{}",
+        instruction,
+        annotation(cpu, &instruction, TraceFormat::Mesen)
+    );
+
+    format!(
+        "{:04X}  {:<32} A:{:02X} X:{:02X} Y:{:02X} S:{:02X} P:{} Cycle:{}",
+        pc,
+        disassembly,
+        cpu.accumulator(),
+        cpu.index_x(),
+        cpu.index_y(),
+        cpu.stack_pointer(),
+        flag_letters(cpu.status().bits()),
+        cpu.cycles()
+    )
+}
+
+fn peek_instruction<B: Bus>(cpu: &mut Cpu<B>) -> (u16, Opcode, [u8; 3]) {
+    let pc = cpu.program_counter();
+    let byte = cpu.bus_mut().peek(pc);
+    let opcode = cpu.variant().opcodes()[byte as usize];
+
+    let mut bytes = [byte, 0, 0];
+    for (offset, slot) in (1..).zip(&mut bytes[1..opcode.bytes as usize]) {
+        *slot = cpu.bus_mut().peek(pc.wrapping_add(offset));
+    }
+    (pc, opcode, bytes)
+}
+
+fn hex_bytes(bytes: &[u8]) -> String {
+    let hex: Vec<String> = bytes.iter().map(|byte| format!("{:02X}", byte)).collect();
+    hex.join(" ")
+}
+
+// Set flags in upper case, clear ones in lower case, from N down to C.
+fn flag_letters(bits: u8) -> String {
+    "nvubdizc"
+        .chars()
+        .enumerate()
+        .map(|(index, letter)| {
+            if bits & (0x80 >> index) != 0 {
+                letter.to_ascii_uppercase()
+            } else {
+                letter
+            }
+        })
+        .collect()
+}
+
+// The effective address of a memory operand and the value there, in the
+// style of the given debugger.
+fn annotation<B: Bus>(
+    cpu: &mut Cpu<B>,
+    instruction: &DecodedInstruction,
+    format: TraceFormat,
+) -> String {
+    let x = cpu.index_x();
+    let y = cpu.index_y();
+
+    // Whether the address differs from the written operand decides if it is
+    // shown alongside the value.
+    let (address, indexed) = match instruction.operand {
+        Operand::ZeroPage(address) => (address as u16, false),
+        Operand::ZeroPageX(address) => (address.wrapping_add(x) as u16, true),
+        Operand::ZeroPageY(address) => (address.wrapping_add(y) as u16, true),
+        Operand::Absolute(address) => match instruction.opcode.instruction {
+            Instruction::Jmp | Instruction::Jsr => return String::new(),
+            _ => (address, false),
+        },
+        Operand::AbsoluteX(address) => (address.wrapping_add(x as u16), true),
+        Operand::AbsoluteY(address) => (address.wrapping_add(y as u16), true),
+        Operand::IndexedIndirect(address) => {
+            let pointer = address.wrapping_add(x);
+            (
+                peek_word(cpu, pointer as u16, pointer.wrapping_add(1) as u16),
+                true,
+            )
+        }
+        Operand::IndirectIndexed(address) => {
+            let base = peek_word(cpu, address as u16, address.wrapping_add(1) as u16);
+            (base.wrapping_add(y as u16), true)
+        }
+        Operand::ZeroPageIndirect(address) => (
+            peek_word(cpu, address as u16, address.wrapping_add(1) as u16),
+            true,
+        ),
+        Operand::ZeroPageRelative { address, .. } => (address as u16, false),
+        _ => return String::new(),
+    };
+    let value = cpu.bus_mut().peek(address);
+
+    match (format, indexed) {
+        (TraceFormat::Mesen, true) => format!(" [${:04X}] = ${:02X}", address, value),
+        (TraceFormat::Mesen, false) => format!(" = ${:02X}", value),
+        (_, true) => format!(" @ ${:04X} = #${:02X}", address, value),
+        (_, false) => format!(" = #${:02X}", value),
+    }
+}
+
+fn nestest_operand<B: Bus>(
+    cpu: &mut Cpu<B>,
+    instruction: &Instruction,
+    mode: &AddressingMode,
+    pc: u16,
+    bytes: &[u8; 3],
+) -> String {
+    let low = bytes[1];
+    let word = u16::from_le_bytes([bytes[1], bytes[2]]);
+
+    match mode {
+        AddressingMode::Implicit => String::new(),
+        AddressingMode::Accumulator => "A".to_string(),
+        AddressingMode::Immediate => format!("#${:02X}", low),
+        AddressingMode::ZeroPage => {
+            let value = cpu.bus_mut().peek(low as u16);
+            format!("${:02X} = {:02X}", low, value)
+        }
+        AddressingMode::ZeroPageX | AddressingMode::ZeroPageY => {
+            let (index, name) = match mode {
+                AddressingMode::ZeroPageX => (cpu.index_x(), 'X'),
+                _ => (cpu.index_y(), 'Y'),
+            };
+            let address = low.wrapping_add(index);
+            let value = cpu.bus_mut().peek(address as u16);
+            format!("${:02X},{} @ {:02X} = {:02X}", low, name, address, value)
+        }
+        AddressingMode::Absolute => match instruction {
+            Instruction::Jmp | Instruction::Jsr => format!("${:04X}", word),
+            _ => {
+                let value = cpu.bus_mut().peek(word);
+                format!("${:04X} = {:02X}", word, value)
+            }
+        },
+        AddressingMode::AbsoluteX | AddressingMode::AbsoluteY => {
+            let (index, name) = match mode {
+                AddressingMode::AbsoluteX => (cpu.index_x(), 'X'),
+                _ => (cpu.index_y(), 'Y'),
+            };
+            let address = word.wrapping_add(index as u16);
+            let value = cpu.bus_mut().peek(address);
+            format!("${:04X},{} @ {:04X} = {:02X}", word, name, address, value)
+        }
+        AddressingMode::Indirect => {
+            let target = peek_word(cpu, word, (word & 0xFF00) | (word.wrapping_add(1) & 0x00FF));
+            format!("(${:04X}) = {:04X}", word, target)
+        }
+        AddressingMode::IndexedIndirect => {
+            let pointer = low.wrapping_add(cpu.index_x());
+            let address = peek_word(cpu, pointer as u16, pointer.wrapping_add(1) as u16);
+            let value = cpu.bus_mut().peek(address);
+            format!(
+                "(${:02X},X) @ {:02X} = {:04X} = {:02X}",
+                low, pointer, address, value
+            )
+        }
+        AddressingMode::IndirectIndexed => {
+            let base = peek_word(cpu, low as u16, low.wrapping_add(1) as u16);
+            let address = base.wrapping_add(cpu.index_y() as u16);
+            let value = cpu.bus_mut().peek(address);
+            format!(
+                "(${:02X}),Y = {:04X} @ {:04X} = {:02X}",
+                low, base, address, value
+            )
+        }
+        AddressingMode::Relative => {
+            let target = pc.wrapping_add(2).wrapping_add(low as i8 as u16);
+            format!("${:04X}", target)
+        }
+        // nestest only covers the NMOS instruction set.
+        AddressingMode::ZeroPageIndirect => format!("(${:02X})", low),
+        AddressingMode::AbsoluteIndexedIndirect => format!("(${:04X},X)", word),
+        AddressingMode::ZeroPageRelative => {
+            let target = pc.wrapping_add(3).wrapping_add(bytes[2] as i8 as u16);
+            format!("${:02X},${:04X}", low, target)
+        }
+    }
+}
+
+fn peek_word<B: Bus>(cpu: &mut Cpu<B>, low: u16, high: u16) -> u16 {
+    let low = cpu.bus_mut().peek(low);
+    let high = cpu.bus_mut().peek(high);
+    u16::from_le_bytes([low, high])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bus::Ram;
+    use crate::status::StatusFlags;
+
+    const START: u16 = 0x0400;
+    const NOPS: [u8; 3] = [0xEA, 0xEA, 0xEA];
+
+    // Counts reads so that tests can tell them apart from peeks.
+    struct PeekBus {
+        ram: Ram,
+        reads: usize,
+    }
+
+    impl Bus for PeekBus {
+        fn read(&mut self, address: u16) -> u8 {
+            self.reads += 1;
+            self.ram.read(address)
+        }
+
+        fn write(&mut self, address: u16, value: u8) {
+            self.ram.write(address, value);
+        }
+
+        fn peek(&mut self, address: u16) -> u8 {
+            self.ram.read(address)
+        }
+    }
+
+    fn traced_cpu(program: &[u8], tracer: Tracer) -> Cpu<PeekBus> {
+        let mut ram = Ram::new();
+        ram.load(START, program);
+        ram.write(0x0011, 0x42);
+        let mut cpu = Cpu::with_bus(PeekBus { ram, reads: 0 });
+        cpu.set_state(CpuState {
+            index_x: 0x01,
+            status: StatusFlags::from_bits(0x24),
+            stack_pointer: 0xFD,
+            program_counter: START,
+            ..CpuState::default()
+        });
+        cpu.set_tracer(tracer);
+        cpu
+    }
+
+    fn run(cpu: &mut Cpu<PeekBus>, instructions: usize) -> Vec<String> {
+        for _ in 0..instructions {
+            cpu.step().unwrap();
+        }
+        cpu.tracer().unwrap().lines().map(String::from).collect()
+    }
+
+    fn traced_pcs(tracer: Tracer) -> Vec<String> {
+        let mut cpu = traced_cpu(&NOPS, tracer);
+        run(&mut cpu, NOPS.len())
+            .iter()
+            .map(|line| line[..4].to_string())
+            .collect()
+    }
+
+    #[test]
+    fn fceux_format() {
+        let mut cpu = traced_cpu(&[0xB5, 0x10], Tracer::ring_buffer(TraceFormat::Fceux, 4));
+        assert_eq!(
+            run(&mut cpu, 1),
+            ["c0          A:00 X:01 Y:00 S:FD P:nvUbdIzc  $0400: B5 10     LDA $10,X @ $0011 = #$42"]
+        );
+    }
+
+    #[test]
+    fn mesen_format() {
+        let mut cpu = traced_cpu(&[0xB5, 0x10], Tracer::ring_buffer(TraceFormat::Mesen, 4));
+        assert_eq!(
+            run(&mut cpu, 1),
+            ["0400  LDA $10,X [$0011] = $42          A:00 X:01 Y:00 S:FD P:nvUbdIzc Cycle:0"]
+        );
+    }
+
+    #[test]
+    fn tracing_only_peeks() {
+        for &format in &[TraceFormat::Nestest, TraceFormat::Fceux, TraceFormat::Mesen] {
+            let mut cpu = traced_cpu(&[0xB5, 0x10], Tracer::ring_buffer(format, 4));
+            let info = cpu.step().unwrap();
+            assert_eq!(cpu.bus().reads, info.cycles as usize);
+        }
+    }
+
+    #[test]
+    fn pc_range_filter() {
+        let tracer = Tracer::ring_buffer(TraceFormat::Mesen, 4)
+            .with_filter(TraceFilter::PcRange(0x0401..=0x0401));
+        assert_eq!(traced_pcs(tracer), ["0401"]);
+    }
+
+    #[test]
+    fn bank_filter() {
+        let tracer = Tracer::ring_buffer(TraceFormat::Mesen, 4).with_filter(TraceFilter::Bank {
+            bank: 0x0201,
+            bank_of: Box::new(|pc| pc as usize >> 1),
+        });
+        assert_eq!(traced_pcs(tracer), ["0402"]);
+    }
+
+    #[test]
+    fn condition_filter() {
+        let tracer = Tracer::ring_buffer(TraceFormat::Mesen, 4).with_filter(
+            TraceFilter::Condition(Box::new(|state| state.program_counter != 0x0401)),
+        );
+        assert_eq!(traced_pcs(tracer), ["0400", "0402"]);
+    }
+
+    #[test]
+    fn filters_must_all_accept() {
+        let tracer = Tracer::ring_buffer(TraceFormat::Mesen, 4)
+            .with_filter(TraceFilter::PcRange(0x0400..=0x0401))
+            .with_filter(TraceFilter::Condition(Box::new(|state| {
+                state.program_counter != 0x0400
+            })));
+        assert_eq!(traced_pcs(tracer), ["0401"]);
+    }
+
+    #[test]
+    fn tracer_is_send() {
+        fn assert_send<T: Send>() {}
+        assert_send::<Tracer>();
+    }
+
+    #[test]
+    fn ring_buffer_evicts_the_oldest_lines() {
+        assert_eq!(
+            traced_pcs(Tracer::ring_buffer(TraceFormat::Mesen, 2)),
+            ["0401", "0402"]
+        );
+        assert!(traced_pcs(Tracer::ring_buffer(TraceFormat::Mesen, 0)).is_empty());
+    }
+}
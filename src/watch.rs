@@ -0,0 +1,248 @@
+//! Memory watches: callbacks and watchpoints on bus accesses.
+
+#[cfg(feature = "alloc")]
+use alloc::boxed::Box;
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+#[cfg(feature = "alloc")]
+use core::ops::RangeInclusive;
+
+/// The kind of bus access a watch matches.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AccessKind {
+    /// A data read, including operand and dummy reads.
+    Read,
+    /// A write, including the dummy writes of read-modify-write instructions.
+    Write,
+    /// An opcode fetch.
+    Execute,
+}
+
+/// One bus access, as passed to watch callbacks.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct MemoryAccess {
+    /// What kind of access it was.
+    pub kind: AccessKind,
+    /// The address on the bus.
+    pub address: u16,
+    /// The byte read or written.
+    pub value: u8,
+    /// The CPU cycle of the access, counted from zero.
+    pub cycle: u64,
+}
+
+/// Identifies a registered watch so that it can be removed. Ids of removed
+/// watches are reused.
+#[cfg(feature = "alloc")]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct WatchId(usize);
+
+#[cfg(feature = "alloc")]
+enum Action {
+    Observe(Box<dyn FnMut(&MemoryAccess) + Send>),
+    Stop,
+}
+
+#[cfg(feature = "alloc")]
+struct Watch {
+    kind: AccessKind,
+    range: RangeInclusive<u16>,
+    action: Action,
+}
+
+// Registered watches, indexed by WatchId. Removed slots are left empty so
+// that ids stay stable.
+#[cfg(feature = "alloc")]
+#[derive(Default)]
+pub(crate) struct Watches {
+    entries: Vec<Option<Watch>>,
+    active: usize,
+    hit: Option<MemoryAccess>,
+}
+
+#[cfg(feature = "alloc")]
+impl Watches {
+    pub(crate) fn is_empty(&self) -> bool {
+        self.active == 0
+    }
+
+    pub(crate) fn observe(
+        &mut self,
+        kind: AccessKind,
+        range: RangeInclusive<u16>,
+        callback: Box<dyn FnMut(&MemoryAccess) + Send>,
+    ) -> WatchId {
+        self.insert(Watch {
+            kind,
+            range,
+            action: Action::Observe(callback),
+        })
+    }
+
+    pub(crate) fn stop(&mut self, kind: AccessKind, range: RangeInclusive<u16>) -> WatchId {
+        self.insert(Watch {
+            kind,
+            range,
+            action: Action::Stop,
+        })
+    }
+
+    pub(crate) fn remove(&mut self, id: WatchId) {
+        if let Some(entry) = self.entries.get_mut(id.0) {
+            if entry.take().is_some() {
+                self.active -= 1;
+            }
+        }
+    }
+
+    pub(crate) fn notify(&mut self, access: MemoryAccess) {
+        for watch in self.entries.iter_mut().flatten() {
+            if watch.kind != access.kind || !watch.range.contains(&access.address) {
+                continue;
+            }
+
+            match &mut watch.action {
+                Action::Observe(callback) => callback(&access),
+                Action::Stop => {
+                    if self.hit.is_none() {
+                        self.hit = Some(access);
+                    }
+                }
+            }
+        }
+    }
+
+    // Returns the first watchpoint hit since the last call.
+    pub(crate) fn take_hit(&mut self) -> Option<MemoryAccess> {
+        self.hit.take()
+    }
+
+    fn insert(&mut self, watch: Watch) -> WatchId {
+        self.active += 1;
+        match self.entries.iter().position(Option::is_none) {
+            Some(index) => {
+                self.entries[index] = Some(watch);
+                WatchId(index)
+            }
+            None => {
+                self.entries.push(Some(watch));
+                WatchId(self.entries.len() - 1)
+            }
+        }
+    }
+}
+
+#[cfg(all(test, feature = "alloc"))]
+mod tests {
+    use super::*;
+    use crate::bus::{Bus, Ram};
+    use crate::cpu::{Cpu, StopReason};
+    use std::sync::{Arc, Mutex};
+
+    const START: u16 = 0x0400;
+
+    // LDA $0300 / STA $0301 / INC $0302, then a JAM.
+    const PROGRAM: [u8; 10] = [0xAD, 0x00, 0x03, 0x8D, 0x01, 0x03, 0xEE, 0x02, 0x03, 0x02];
+
+    fn program_cpu() -> Cpu<Ram> {
+        let mut ram = Ram::new();
+        ram.load(START, &PROGRAM);
+        ram.write(0x0300, 0x07);
+        ram.write(0x0302, 0x41);
+        let mut cpu = Cpu::with_bus(ram);
+        cpu.set_program_counter(START);
+        cpu
+    }
+
+    fn record(
+        cpu: &mut Cpu<Ram>,
+        kind: AccessKind,
+        range: RangeInclusive<u16>,
+    ) -> (WatchId, Arc<Mutex<Vec<MemoryAccess>>>) {
+        let log = Arc::new(Mutex::new(Vec::new()));
+        let sink = Arc::clone(&log);
+        let id = cpu.add_watch(kind, range, move |access| {
+            sink.lock().unwrap().push(*access)
+        });
+        (id, log)
+    }
+
+    fn access(kind: AccessKind, address: u16, value: u8, cycle: u64) -> MemoryAccess {
+        MemoryAccess {
+            kind,
+            address,
+            value,
+            cycle,
+        }
+    }
+
+    #[test]
+    fn callbacks_see_each_kind_with_its_cycle() {
+        let mut cpu = program_cpu();
+        let (_, reads) = record(&mut cpu, AccessKind::Read, 0x0300..=0x0302);
+        let (_, writes) = record(&mut cpu, AccessKind::Write, 0x0300..=0x0302);
+        let (_, fetches) = record(&mut cpu, AccessKind::Execute, 0x0403..=0x0403);
+        for _ in 0..3 {
+            cpu.step().unwrap();
+        }
+
+        let expected = [
+            access(AccessKind::Read, 0x0300, 0x07, 3),
+            access(AccessKind::Read, 0x0302, 0x41, 11),
+        ];
+        assert_eq!(*reads.lock().unwrap(), expected);
+        let expected = [
+            access(AccessKind::Write, 0x0301, 0x07, 7),
+            access(AccessKind::Write, 0x0302, 0x41, 12),
+            access(AccessKind::Write, 0x0302, 0x42, 13),
+        ];
+        assert_eq!(*writes.lock().unwrap(), expected);
+        let expected = [access(AccessKind::Execute, 0x0403, 0x8D, 4)];
+        assert_eq!(*fetches.lock().unwrap(), expected);
+    }
+
+    #[test]
+    fn accesses_outside_the_range_are_ignored() {
+        let mut cpu = program_cpu();
+        let (_, log) = record(&mut cpu, AccessKind::Read, 0x0303..=0x03FF);
+        for _ in 0..3 {
+            cpu.step().unwrap();
+        }
+        assert!(log.lock().unwrap().is_empty());
+    }
+
+    #[test]
+    fn removed_slots_are_reused() {
+        let mut cpu = program_cpu();
+        let (first, removed) = record(&mut cpu, AccessKind::Read, 0x0300..=0x0300);
+        let (second, _) = record(&mut cpu, AccessKind::Read, 0x0300..=0x0300);
+        cpu.remove_watch(first);
+        let (third, _) = record(&mut cpu, AccessKind::Read, 0x0300..=0x0300);
+        assert_eq!(third, first);
+        assert_ne!(third, second);
+
+        cpu.step().unwrap();
+        assert!(removed.lock().unwrap().is_empty());
+    }
+
+    #[test]
+    fn watchpoints_stop_run_after_the_instruction() {
+        let mut cpu = program_cpu();
+        cpu.add_watchpoint(AccessKind::Write, 0x0301..=0x0301);
+        let expected = access(AccessKind::Write, 0x0301, 0x07, 7);
+        assert_eq!(cpu.run(), StopReason::Watchpoint(expected));
+        assert_eq!(cpu.program_counter(), 0x0406);
+    }
+
+    #[test]
+    fn ticking_reports_each_access_once() {
+        let mut cpu = program_cpu();
+        let (_, log) = record(&mut cpu, AccessKind::Write, 0x0302..=0x0302);
+        cpu.step().unwrap();
+        cpu.step().unwrap();
+        while cpu.tick().unwrap().is_none() {}
+
+        let values: Vec<u8> = log.lock().unwrap().iter().map(|a| a.value).collect();
+        assert_eq!(values, [0x41, 0x42]);
+    }
+}
@@ -0,0 +1,56 @@
+// Helpers shared by the conformance harnesses. Their test files are not
+// distributed with the crate; tests/roms/README.md lists where to get them.
+
+// Each harness uses only some of these.
+#![allow(dead_code)]
+
+use std::env;
+use std::fs;
+use std::path::Path;
+
+pub const REQUIRE_ROMS: &str = "GOOMBA_REQUIRE_ROMS";
+
+pub const HEADER_SIZE: usize = 16;
+pub const PRG_BANK_SIZE: usize = 0x4000;
+
+// Whether every path exists. A missing one fails the test when
+// GOOMBA_REQUIRE_ROMS is set and is otherwise reported as a skip.
+pub fn available(paths: &[&Path]) -> bool {
+    let missing: Vec<String> = paths
+        .iter()
+        .filter(|path| !path.exists())
+        .map(|path| path.display().to_string())
+        .collect();
+    if missing.is_empty() {
+        return true;
+    }
+
+    let missing = missing.join(", ");
+    if env::var_os(REQUIRE_ROMS).is_some() {
+        panic!("{} not found and {} is set", missing, REQUIRE_ROMS);
+    }
+    eprintln!("skipping: {} not found", missing);
+    false
+}
+
+// The parts of an iNES image the harnesses need.
+pub struct Ines {
+    pub mapper: u8,
+    pub prg: Vec<u8>,
+}
+
+pub fn read_ines(path: &Path) -> Ines {
+    let rom = fs::read(path).expect("failed to read iNES image");
+    assert_eq!(
+        &rom[..4],
+        b"NES\x1A",
+        "{} is not an iNES image",
+        path.display()
+    );
+
+    let banks = rom[4] as usize;
+    Ines {
+        mapper: (rom[6] >> 4) | (rom[7] & 0xF0),
+        prg: rom[HEADER_SIZE..HEADER_SIZE + banks * PRG_BANK_SIZE].to_vec(),
+    }
+}
@@ -0,0 +1,301 @@
+// Blargg's cpu_interrupts_v2 single-test ROMs, run cycle by cycle on just
+// enough of an NES to drive them: the PPU's vblank NMI, the APU frame IRQ
+// and the CPU stall of OAM DMA, all timed from the CPU cycles. The ROMs are
+// expected under tests/roms/cpu_interrupts/.
+
+mod common;
+
+use common::{Ines, PRG_BANK_SIZE};
+use goomba::bus::Bus;
+use goomba::cpu::{Cpu, CpuVariant};
+use std::path::Path;
+
+const ROOT: &str = "tests/roms/cpu_interrupts";
+
+// PPU timing with rendering off, in dots: three per CPU cycle.
+const DOTS_PER_SCANLINE: u32 = 341;
+const DOTS_PER_FRAME: u32 = DOTS_PER_SCANLINE * 262;
+const VBLANK_START: u32 = DOTS_PER_SCANLINE * 241 + 1;
+const VBLANK_END: u32 = DOTS_PER_SCANLINE * 261 + 1;
+
+// The 4-step frame sequence raises its IRQ this many CPU cycles after it
+// starts and then repeats.
+const FRAME_IRQ: u64 = 29_829;
+const FRAME_LENGTH: u64 = 29_830;
+
+// The test shell reports through PRG RAM: a status byte that stays at $80
+// while running, a signature, then a NUL-terminated message.
+const STATUS: usize = 0x0000;
+const SIGNATURE: [u8; 3] = [0xDE, 0xB0, 0x61];
+const MESSAGE: usize = 0x0004;
+const RUNNING: u8 = 0x80;
+
+// OAM DMA halts the CPU for one cycle, waits one more if it starts on an
+// odd cycle, then copies 256 bytes in 512 cycles.
+const OAM_DMA_CYCLES: u32 = 513;
+
+const CYCLE_LIMIT: u64 = 60_000_000;
+const CHECK_INTERVAL: u64 = 1_000;
+
+// NROM or MMC1 PRG ROM, PRG RAM, internal RAM, the PPU status and control
+// registers, the APU frame counter and the OAM DMA register.
+struct NesBus {
+    ram: [u8; 0x0800],
+    prg_ram: [u8; 0x2000],
+    prg: Vec<u8>,
+    mmc1: Option<Mmc1>,
+    cycles: u64,
+    dot: u32,
+    vblank: bool,
+    nmi_enabled: bool,
+    nmi_pending: bool,
+    frame_start: u64,
+    frame_irq_inhibit: bool,
+    frame_irq: bool,
+    dma_requested: bool,
+}
+
+#[derive(Default)]
+struct Mmc1 {
+    shift: u8,
+    writes: u8,
+    control: u8,
+    prg_bank: u8,
+}
+
+impl NesBus {
+    fn new(rom: Ines) -> NesBus {
+        let mmc1 = match rom.mapper {
+            0 => None,
+            1 => Some(Mmc1 {
+                control: 0x0C,
+                ..Mmc1::default()
+            }),
+            mapper => panic!("unsupported mapper {}", mapper),
+        };
+
+        NesBus {
+            ram: [0; 0x0800],
+            prg_ram: [0; 0x2000],
+            prg: rom.prg,
+            mmc1,
+            cycles: 0,
+            dot: 0,
+            vblank: false,
+            nmi_enabled: false,
+            nmi_pending: false,
+            frame_start: 0,
+            frame_irq_inhibit: false,
+            frame_irq: false,
+            dma_requested: false,
+        }
+    }
+
+    // Every access is one CPU cycle, so the PPU and APU advance here. Stalled
+    // cycles make no access and are caught up from the CPU's count.
+    fn clock(&mut self) {
+        self.cycles += 1;
+        for _ in 0..3 {
+            self.dot = (self.dot + 1) % DOTS_PER_FRAME;
+            if self.dot == VBLANK_START {
+                self.vblank = true;
+                self.nmi_pending |= self.nmi_enabled;
+            } else if self.dot == VBLANK_END {
+                self.vblank = false;
+            }
+        }
+
+        let elapsed = self.cycles - self.frame_start;
+        if elapsed >= FRAME_IRQ && (elapsed - FRAME_IRQ).is_multiple_of(FRAME_LENGTH) {
+            self.frame_irq |= !self.frame_irq_inhibit;
+        }
+    }
+
+    fn prg_offset(&self, address: u16) -> usize {
+        let offset = address as usize & 0x3FFF;
+        let last = self.prg.len() / PRG_BANK_SIZE - 1;
+        let bank = match self.mmc1 {
+            None => (address as usize - 0x8000) / PRG_BANK_SIZE,
+            Some(ref mmc1) => {
+                let selected = (mmc1.prg_bank & 0x0F) as usize;
+                let upper = address >= 0xC000;
+                match (mmc1.control >> 2) & 0x03 {
+                    0 | 1 => (selected & !1) | upper as usize,
+                    2 if upper => selected,
+                    2 => 0,
+                    _ if upper => last,
+                    _ => selected,
+                }
+            }
+        };
+        (bank * PRG_BANK_SIZE + offset) % self.prg.len()
+    }
+
+    fn write_mmc1(&mut self, address: u16, value: u8) {
+        let mmc1 = match self.mmc1 {
+            Some(ref mut mmc1) => mmc1,
+            None => return,
+        };
+
+        if value & 0x80 != 0 {
+            mmc1.shift = 0;
+            mmc1.writes = 0;
+            mmc1.control |= 0x0C;
+            return;
+        }
+
+        mmc1.shift |= (value & 1) << mmc1.writes;
+        mmc1.writes += 1;
+        if mmc1.writes == 5 {
+            match address {
+                0x8000..=0x9FFF => mmc1.control = mmc1.shift,
+                0xE000..=0xFFFF => mmc1.prg_bank = mmc1.shift,
+                _ => {}
+            }
+            mmc1.shift = 0;
+            mmc1.writes = 0;
+        }
+    }
+
+    fn result(&self) -> Option<u8> {
+        let status = self.prg_ram[STATUS];
+        if self.prg_ram[STATUS + 1..MESSAGE] == SIGNATURE && status != RUNNING {
+            Some(status)
+        } else {
+            None
+        }
+    }
+
+    fn message(&self) -> String {
+        self.prg_ram[MESSAGE..]
+            .iter()
+            .take_while(|&&byte| byte != 0)
+            .map(|&byte| byte as char)
+            .collect()
+    }
+}
+
+impl Bus for NesBus {
+    fn read(&mut self, address: u16) -> u8 {
+        self.clock();
+        match address {
+            0x2000..=0x3FFF if address & 0x07 == 0x02 => {
+                let status = (self.vblank as u8) << 7;
+                self.vblank = false;
+                status
+            }
+            0x4015 => {
+                let status = (self.frame_irq as u8) << 6;
+                self.frame_irq = false;
+                status
+            }
+            _ => self.peek(address),
+        }
+    }
+
+    // Leaves time and the status flags alone.
+    fn peek(&mut self, address: u16) -> u8 {
+        match address {
+            0x0000..=0x1FFF => self.ram[address as usize & 0x07FF],
+            0x2000..=0x3FFF if address & 0x07 == 0x02 => (self.vblank as u8) << 7,
+            0x4015 => (self.frame_irq as u8) << 6,
+            0x6000..=0x7FFF => self.prg_ram[address as usize - 0x6000],
+            0x8000..=0xFFFF => self.prg[self.prg_offset(address)],
+            _ => 0xFF,
+        }
+    }
+
+    fn write(&mut self, address: u16, value: u8) {
+        self.clock();
+        match address {
+            0x0000..=0x1FFF => self.ram[address as usize & 0x07FF] = value,
+            0x2000..=0x3FFF if address & 0x07 == 0x00 => {
+                let enabled = value & 0x80 != 0;
+                self.nmi_pending |= enabled && !self.nmi_enabled && self.vblank;
+                self.nmi_enabled = enabled;
+            }
+            // The copied bytes only matter to the PPU, which is not modelled.
+            0x4014 => self.dma_requested = true,
+            0x4017 => {
+                self.frame_start = self.cycles;
+                self.frame_irq_inhibit = value & 0xC0 != 0;
+                if self.frame_irq_inhibit {
+                    self.frame_irq = false;
+                }
+            }
+            0x6000..=0x7FFF => self.prg_ram[address as usize - 0x6000] = value,
+            0x8000..=0xFFFF => self.write_mmc1(address, value),
+            _ => {}
+        }
+    }
+}
+
+fn run(name: &str) {
+    let path = Path::new(ROOT).join(name);
+    if !common::available(&[&path]) {
+        return;
+    }
+
+    let rom = common::read_ines(&path);
+    let mut cpu = Cpu::with_variant(CpuVariant::Ricoh2A03, NesBus::new(rom));
+    cpu.power_on();
+
+    // Ticking lets the interrupt lines change between any two cycles.
+    loop {
+        if let Err(error) = cpu.tick() {
+            panic!("{}: {}", name, error);
+        }
+
+        let elapsed = cpu.cycles();
+        let bus = cpu.bus_mut();
+        while bus.cycles < elapsed {
+            bus.clock();
+        }
+        let dma = bus.dma_requested;
+        bus.dma_requested = false;
+        let nmi = bus.nmi_pending;
+        bus.nmi_pending = false;
+        let irq = bus.frame_irq;
+        let cycles = bus.cycles;
+        if nmi {
+            cpu.nmi();
+        }
+        cpu.set_irq(irq);
+        if dma {
+            cpu.stall(OAM_DMA_CYCLES + (cycles % 2) as u32);
+        }
+
+        if cycles.is_multiple_of(CHECK_INTERVAL) {
+            if let Some(status) = cpu.bus().result() {
+                assert_eq!(status, 0, "{} failed: {}", name, cpu.bus().message());
+                return;
+            }
+            assert!(cycles < CYCLE_LIMIT, "{} did not finish", name);
+        }
+    }
+}
+
+#[test]
+fn cli_latency() {
+    run("1-cli_latency.nes");
+}
+
+#[test]
+fn nmi_and_brk() {
+    run("2-nmi_and_brk.nes");
+}
+
+#[test]
+fn nmi_and_irq() {
+    run("3-nmi_and_irq.nes");
+}
+
+#[test]
+fn irq_and_dma() {
+    run("4-irq_and_dma.nes");
+}
+
+#[test]
+fn branch_delays_irq() {
+    run("5-branch_delays_irq.nes");
+}
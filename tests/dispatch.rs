@@ -0,0 +1,459 @@
+// Times opcode dispatch through the const tables against a match over the
+// opcode byte, which is how decoding worked before the tables. Run with
+// `cargo test --release --test dispatch -- --ignored --nocapture`.
+
+use goomba::cpu::CpuVariant;
+use goomba::opcode::{AddressingMode as M, Instruction as I, Opcode};
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+const STREAM_LENGTH: usize = 4096;
+const ROUNDS: usize = 20_000;
+
+fn decode_match(cmos: bool, byte: u8) -> Opcode {
+    if cmos {
+        if let Some(opcode) = decode_cmos(byte) {
+            return opcode;
+        }
+    }
+
+    match byte {
+        0x00 => Opcode::new(I::Brk, M::Implicit, 1, 7),
+        0x01 => Opcode::new(I::Ora, M::IndexedIndirect, 2, 6),
+        0x02 => Opcode::undocumented(I::Jam, M::Implicit, 1, 2),
+        0x03 => Opcode::undocumented(I::Slo, M::IndexedIndirect, 2, 8),
+        0x04 => Opcode::undocumented(I::Nop, M::ZeroPage, 2, 3),
+        0x05 => Opcode::new(I::Ora, M::ZeroPage, 2, 3),
+        0x06 => Opcode::new(I::Asl, M::ZeroPage, 2, 5),
+        0x07 => Opcode::undocumented(I::Slo, M::ZeroPage, 2, 5),
+        0x08 => Opcode::new(I::Php, M::Implicit, 1, 3),
+        0x09 => Opcode::new(I::Ora, M::Immediate, 2, 2),
+        0x0A => Opcode::new(I::Asl, M::Accumulator, 1, 2),
+        0x0B => Opcode::undocumented(I::Anc, M::Immediate, 2, 2),
+        0x0C => Opcode::undocumented(I::Nop, M::Absolute, 3, 4),
+        0x0D => Opcode::new(I::Ora, M::Absolute, 3, 4),
+        0x0E => Opcode::new(I::Asl, M::Absolute, 3, 6),
+        0x0F => Opcode::undocumented(I::Slo, M::Absolute, 3, 6),
+        0x10 => Opcode::new(I::Bpl, M::Relative, 2, 2),
+        0x11 => Opcode::new(I::Ora, M::IndirectIndexed, 2, 5),
+        0x12 => Opcode::undocumented(I::Jam, M::Implicit, 1, 2),
+        0x13 => Opcode::undocumented(I::Slo, M::IndirectIndexed, 2, 8),
+        0x14 => Opcode::undocumented(I::Nop, M::ZeroPageX, 2, 4),
+        0x15 => Opcode::new(I::Ora, M::ZeroPageX, 2, 4),
+        0x16 => Opcode::new(I::Asl, M::ZeroPageX, 2, 6),
+        0x17 => Opcode::undocumented(I::Slo, M::ZeroPageX, 2, 6),
+        0x18 => Opcode::new(I::Clc, M::Implicit, 1, 2),
+        0x19 => Opcode::new(I::Ora, M::AbsoluteY, 3, 4),
+        0x1A => Opcode::undocumented(I::Nop, M::Implicit, 1, 2),
+        0x1B => Opcode::undocumented(I::Slo, M::AbsoluteY, 3, 7),
+        0x1C => Opcode::undocumented(I::Nop, M::AbsoluteX, 3, 4),
+        0x1D => Opcode::new(I::Ora, M::AbsoluteX, 3, 4),
+        0x1E => Opcode::new(I::Asl, M::AbsoluteX, 3, 7),
+        0x1F => Opcode::undocumented(I::Slo, M::AbsoluteX, 3, 7),
+        0x20 => Opcode::new(I::Jsr, M::Absolute, 3, 6),
+        0x21 => Opcode::new(I::And, M::IndexedIndirect, 2, 6),
+        0x22 => Opcode::undocumented(I::Jam, M::Implicit, 1, 2),
+        0x23 => Opcode::undocumented(I::Rla, M::IndexedIndirect, 2, 8),
+        0x24 => Opcode::new(I::Bit, M::ZeroPage, 2, 3),
+        0x25 => Opcode::new(I::And, M::ZeroPage, 2, 3),
+        0x26 => Opcode::new(I::Rol, M::ZeroPage, 2, 5),
+        0x27 => Opcode::undocumented(I::Rla, M::ZeroPage, 2, 5),
+        0x28 => Opcode::new(I::Plp, M::Implicit, 1, 4),
+        0x29 => Opcode::new(I::And, M::Immediate, 2, 2),
+        0x2A => Opcode::new(I::Rol, M::Accumulator, 1, 2),
+        0x2B => Opcode::undocumented(I::Anc, M::Immediate, 2, 2),
+        0x2C => Opcode::new(I::Bit, M::Absolute, 3, 4),
+        0x2D => Opcode::new(I::And, M::Absolute, 3, 4),
+        0x2E => Opcode::new(I::Rol, M::Absolute, 3, 6),
+        0x2F => Opcode::undocumented(I::Rla, M::Absolute, 3, 6),
+        0x30 => Opcode::new(I::Bmi, M::Relative, 2, 2),
+        0x31 => Opcode::new(I::And, M::IndirectIndexed, 2, 5),
+        0x32 => Opcode::undocumented(I::Jam, M::Implicit, 1, 2),
+        0x33 => Opcode::undocumented(I::Rla, M::IndirectIndexed, 2, 8),
+        0x34 => Opcode::undocumented(I::Nop, M::ZeroPageX, 2, 4),
+        0x35 => Opcode::new(I::And, M::ZeroPageX, 2, 4),
+        0x36 => Opcode::new(I::Rol, M::ZeroPageX, 2, 6),
+        0x37 => Opcode::undocumented(I::Rla, M::ZeroPageX, 2, 6),
+        0x38 => Opcode::new(I::Sec, M::Implicit, 1, 2),
+        0x39 => Opcode::new(I::And, M::AbsoluteY, 3, 4),
+        0x3A => Opcode::undocumented(I::Nop, M::Implicit, 1, 2),
+        0x3B => Opcode::undocumented(I::Rla, M::AbsoluteY, 3, 7),
+        0x3C => Opcode::undocumented(I::Nop, M::AbsoluteX, 3, 4),
+        0x3D => Opcode::new(I::And, M::AbsoluteX, 3, 4),
+        0x3E => Opcode::new(I::Rol, M::AbsoluteX, 3, 7),
+        0x3F => Opcode::undocumented(I::Rla, M::AbsoluteX, 3, 7),
+        0x40 => Opcode::new(I::Rti, M::Implicit, 1, 6),
+        0x41 => Opcode::new(I::Eor, M::IndexedIndirect, 2, 6),
+        0x42 => Opcode::undocumented(I::Jam, M::Implicit, 1, 2),
+        0x43 => Opcode::undocumented(I::Sre, M::IndexedIndirect, 2, 8),
+        0x44 => Opcode::undocumented(I::Nop, M::ZeroPage, 2, 3),
+        0x45 => Opcode::new(I::Eor, M::ZeroPage, 2, 3),
+        0x46 => Opcode::new(I::Lsr, M::ZeroPage, 2, 5),
+        0x47 => Opcode::undocumented(I::Sre, M::ZeroPage, 2, 5),
+        0x48 => Opcode::new(I::Pha, M::Implicit, 1, 3),
+        0x49 => Opcode::new(I::Eor, M::Immediate, 2, 2),
+        0x4A => Opcode::new(I::Lsr, M::Accumulator, 1, 2),
+        0x4B => Opcode::undocumented(I::Alr, M::Immediate, 2, 2),
+        0x4C => Opcode::new(I::Jmp, M::Absolute, 3, 3),
+        0x4D => Opcode::new(I::Eor, M::Absolute, 3, 4),
+        0x4E => Opcode::new(I::Lsr, M::Absolute, 3, 6),
+        0x4F => Opcode::undocumented(I::Sre, M::Absolute, 3, 6),
+        0x50 => Opcode::new(I::Bvc, M::Relative, 2, 2),
+        0x51 => Opcode::new(I::Eor, M::IndirectIndexed, 2, 5),
+        0x52 => Opcode::undocumented(I::Jam, M::Implicit, 1, 2),
+        0x53 => Opcode::undocumented(I::Sre, M::IndirectIndexed, 2, 8),
+        0x54 => Opcode::undocumented(I::Nop, M::ZeroPageX, 2, 4),
+        0x55 => Opcode::new(I::Eor, M::ZeroPageX, 2, 4),
+        0x56 => Opcode::new(I::Lsr, M::ZeroPageX, 2, 6),
+        0x57 => Opcode::undocumented(I::Sre, M::ZeroPageX, 2, 6),
+        0x58 => Opcode::new(I::Cli, M::Implicit, 1, 2),
+        0x59 => Opcode::new(I::Eor, M::AbsoluteY, 3, 4),
+        0x5A => Opcode::undocumented(I::Nop, M::Implicit, 1, 2),
+        0x5B => Opcode::undocumented(I::Sre, M::AbsoluteY, 3, 7),
+        0x5C => Opcode::undocumented(I::Nop, M::AbsoluteX, 3, 4),
+        0x5D => Opcode::new(I::Eor, M::AbsoluteX, 3, 4),
+        0x5E => Opcode::new(I::Lsr, M::AbsoluteX, 3, 7),
+        0x5F => Opcode::undocumented(I::Sre, M::AbsoluteX, 3, 7),
+        0x60 => Opcode::new(I::Rts, M::Implicit, 1, 6),
+        0x61 => Opcode::new(I::Adc, M::IndexedIndirect, 2, 6),
+        0x62 => Opcode::undocumented(I::Jam, M::Implicit, 1, 2),
+        0x63 => Opcode::undocumented(I::Rra, M::IndexedIndirect, 2, 8),
+        0x64 => Opcode::undocumented(I::Nop, M::ZeroPage, 2, 3),
+        0x65 => Opcode::new(I::Adc, M::ZeroPage, 2, 3),
+        0x66 => Opcode::new(I::Ror, M::ZeroPage, 2, 5),
+        0x67 => Opcode::undocumented(I::Rra, M::ZeroPage, 2, 5),
+        0x68 => Opcode::new(I::Pla, M::Implicit, 1, 4),
+        0x69 => Opcode::new(I::Adc, M::Immediate, 2, 2),
+        0x6A => Opcode::new(I::Ror, M::Accumulator, 1, 2),
+        0x6B => Opcode::undocumented(I::Arr, M::Immediate, 2, 2),
+        0x6C => Opcode::new(I::Jmp, M::Indirect, 3, 5),
+        0x6D => Opcode::new(I::Adc, M::Absolute, 3, 4),
+        0x6E => Opcode::new(I::Ror, M::Absolute, 3, 6),
+        0x6F => Opcode::undocumented(I::Rra, M::Absolute, 3, 6),
+        0x70 => Opcode::new(I::Bvs, M::Relative, 2, 2),
+        0x71 => Opcode::new(I::Adc, M::IndirectIndexed, 2, 5),
+        0x72 => Opcode::undocumented(I::Jam, M::Implicit, 1, 2),
+        0x73 => Opcode::undocumented(I::Rra, M::IndirectIndexed, 2, 8),
+        0x74 => Opcode::undocumented(I::Nop, M::ZeroPageX, 2, 4),
+        0x75 => Opcode::new(I::Adc, M::ZeroPageX, 2, 4),
+        0x76 => Opcode::new(I::Ror, M::ZeroPageX, 2, 6),
+        0x77 => Opcode::undocumented(I::Rra, M::ZeroPageX, 2, 6),
+        0x78 => Opcode::new(I::Sei, M::Implicit, 1, 2),
+        0x79 => Opcode::new(I::Adc, M::AbsoluteY, 3, 4),
+        0x7A => Opcode::undocumented(I::Nop, M::Implicit, 1, 2),
+        0x7B => Opcode::undocumented(I::Rra, M::AbsoluteY, 3, 7),
+        0x7C => Opcode::undocumented(I::Nop, M::AbsoluteX, 3, 4),
+        0x7D => Opcode::new(I::Adc, M::AbsoluteX, 3, 4),
+        0x7E => Opcode::new(I::Ror, M::AbsoluteX, 3, 7),
+        0x7F => Opcode::undocumented(I::Rra, M::AbsoluteX, 3, 7),
+        0x80 => Opcode::undocumented(I::Nop, M::Immediate, 2, 2),
+        0x81 => Opcode::new(I::Sta, M::IndexedIndirect, 2, 6),
+        0x82 => Opcode::undocumented(I::Nop, M::Immediate, 2, 2),
+        0x83 => Opcode::undocumented(I::Sax, M::IndexedIndirect, 2, 6),
+        0x84 => Opcode::new(I::Sty, M::ZeroPage, 2, 3),
+        0x85 => Opcode::new(I::Sta, M::ZeroPage, 2, 3),
+        0x86 => Opcode::new(I::Stx, M::ZeroPage, 2, 3),
+        0x87 => Opcode::undocumented(I::Sax, M::ZeroPage, 2, 3),
+        0x88 => Opcode::new(I::Dey, M::Implicit, 1, 2),
+        0x89 => Opcode::undocumented(I::Nop, M::Immediate, 2, 2),
+        0x8A => Opcode::new(I::Txa, M::Implicit, 1, 2),
+        0x8B => Opcode::undocumented(I::Xaa, M::Immediate, 2, 2),
+        0x8C => Opcode::new(I::Sty, M::Absolute, 3, 4),
+        0x8D => Opcode::new(I::Sta, M::Absolute, 3, 4),
+        0x8E => Opcode::new(I::Stx, M::Absolute, 3, 4),
+        0x8F => Opcode::undocumented(I::Sax, M::Absolute, 3, 4),
+        0x90 => Opcode::new(I::Bcc, M::Relative, 2, 2),
+        0x91 => Opcode::new(I::Sta, M::IndirectIndexed, 2, 6),
+        0x92 => Opcode::undocumented(I::Jam, M::Implicit, 1, 2),
+        0x93 => Opcode::undocumented(I::Sha, M::IndirectIndexed, 2, 6),
+        0x94 => Opcode::new(I::Sty, M::ZeroPageX, 2, 4),
+        0x95 => Opcode::new(I::Sta, M::ZeroPageX, 2, 4),
+        0x96 => Opcode::new(I::Stx, M::ZeroPageY, 2, 4),
+        0x97 => Opcode::undocumented(I::Sax, M::ZeroPageY, 2, 4),
+        0x98 => Opcode::new(I::Tya, M::Implicit, 1, 2),
+        0x99 => Opcode::new(I::Sta, M::AbsoluteY, 3, 5),
+        0x9A => Opcode::new(I::Txs, M::Implicit, 1, 2),
+        0x9B => Opcode::undocumented(I::Tas, M::AbsoluteY, 3, 5),
+        0x9C => Opcode::undocumented(I::Shy, M::AbsoluteX, 3, 5),
+        0x9D => Opcode::new(I::Sta, M::AbsoluteX, 3, 5),
+        0x9E => Opcode::undocumented(I::Shx, M::AbsoluteY, 3, 5),
+        0x9F => Opcode::undocumented(I::Sha, M::AbsoluteY, 3, 5),
+        0xA0 => Opcode::new(I::Ldy, M::Immediate, 2, 2),
+        0xA1 => Opcode::new(I::Lda, M::IndexedIndirect, 2, 6),
+        0xA2 => Opcode::new(I::Ldx, M::Immediate, 2, 2),
+        0xA3 => Opcode::undocumented(I::Lax, M::IndexedIndirect, 2, 6),
+        0xA4 => Opcode::new(I::Ldy, M::ZeroPage, 2, 3),
+        0xA5 => Opcode::new(I::Lda, M::ZeroPage, 2, 3),
+        0xA6 => Opcode::new(I::Ldx, M::ZeroPage, 2, 3),
+        0xA7 => Opcode::undocumented(I::Lax, M::ZeroPage, 2, 3),
+        0xA8 => Opcode::new(I::Tay, M::Implicit, 1, 2),
+        0xA9 => Opcode::new(I::Lda, M::Immediate, 2, 2),
+        0xAA => Opcode::new(I::Tax, M::Implicit, 1, 2),
+        0xAB => Opcode::undocumented(I::Lxa, M::Immediate, 2, 2),
+        0xAC => Opcode::new(I::Ldy, M::Absolute, 3, 4),
+        0xAD => Opcode::new(I::Lda, M::Absolute, 3, 4),
+        0xAE => Opcode::new(I::Ldx, M::Absolute, 3, 4),
+        0xAF => Opcode::undocumented(I::Lax, M::Absolute, 3, 4),
+        0xB0 => Opcode::new(I::Bcs, M::Relative, 2, 2),
+        0xB1 => Opcode::new(I::Lda, M::IndirectIndexed, 2, 5),
+        0xB2 => Opcode::undocumented(I::Jam, M::Implicit, 1, 2),
+        0xB3 => Opcode::undocumented(I::Lax, M::IndirectIndexed, 2, 5),
+        0xB4 => Opcode::new(I::Ldy, M::ZeroPageX, 2, 4),
+        0xB5 => Opcode::new(I::Lda, M::ZeroPageX, 2, 4),
+        0xB6 => Opcode::new(I::Ldx, M::ZeroPageY, 2, 4),
+        0xB7 => Opcode::undocumented(I::Lax, M::ZeroPageY, 2, 4),
+        0xB8 => Opcode::new(I::Clv, M::Implicit, 1, 2),
+        0xB9 => Opcode::new(I::Lda, M::AbsoluteY, 3, 4),
+        0xBA => Opcode::new(I::Tsx, M::Implicit, 1, 2),
+        0xBB => Opcode::undocumented(I::Las, M::AbsoluteY, 3, 4),
+        0xBC => Opcode::new(I::Ldy, M::AbsoluteX, 3, 4),
+        0xBD => Opcode::new(I::Lda, M::AbsoluteX, 3, 4),
+        0xBE => Opcode::new(I::Ldx, M::AbsoluteY, 3, 4),
+        0xBF => Opcode::undocumented(I::Lax, M::AbsoluteY, 3, 4),
+        0xC0 => Opcode::new(I::Cpy, M::Immediate, 2, 2),
+        0xC1 => Opcode::new(I::Cmp, M::IndexedIndirect, 2, 6),
+        0xC2 => Opcode::undocumented(I::Nop, M::Immediate, 2, 2),
+        0xC3 => Opcode::undocumented(I::Dcp, M::IndexedIndirect, 2, 8),
+        0xC4 => Opcode::new(I::Cpy, M::ZeroPage, 2, 3),
+        0xC5 => Opcode::new(I::Cmp, M::ZeroPage, 2, 3),
+        0xC6 => Opcode::new(I::Dec, M::ZeroPage, 2, 5),
+        0xC7 => Opcode::undocumented(I::Dcp, M::ZeroPage, 2, 5),
+        0xC8 => Opcode::new(I::Iny, M::Implicit, 1, 2),
+        0xC9 => Opcode::new(I::Cmp, M::Immediate, 2, 2),
+        0xCA => Opcode::new(I::Dex, M::Implicit, 1, 2),
+        0xCB => Opcode::undocumented(I::Sbx, M::Immediate, 2, 2),
+        0xCC => Opcode::new(I::Cpy, M::Absolute, 3, 4),
+        0xCD => Opcode::new(I::Cmp, M::Absolute, 3, 4),
+        0xCE => Opcode::new(I::Dec, M::Absolute, 3, 6),
+        0xCF => Opcode::undocumented(I::Dcp, M::Absolute, 3, 6),
+        0xD0 => Opcode::new(I::Bne, M::Relative, 2, 2),
+        0xD1 => Opcode::new(I::Cmp, M::IndirectIndexed, 2, 5),
+        0xD2 => Opcode::undocumented(I::Jam, M::Implicit, 1, 2),
+        0xD3 => Opcode::undocumented(I::Dcp, M::IndirectIndexed, 2, 8),
+        0xD4 => Opcode::undocumented(I::Nop, M::ZeroPageX, 2, 4),
+        0xD5 => Opcode::new(I::Cmp, M::ZeroPageX, 2, 4),
+        0xD6 => Opcode::new(I::Dec, M::ZeroPageX, 2, 6),
+        0xD7 => Opcode::undocumented(I::Dcp, M::ZeroPageX, 2, 6),
+        0xD8 => Opcode::new(I::Cld, M::Implicit, 1, 2),
+        0xD9 => Opcode::new(I::Cmp, M::AbsoluteY, 3, 4),
+        0xDA => Opcode::undocumented(I::Nop, M::Implicit, 1, 2),
+        0xDB => Opcode::undocumented(I::Dcp, M::AbsoluteY, 3, 7),
+        0xDC => Opcode::undocumented(I::Nop, M::AbsoluteX, 3, 4),
+        0xDD => Opcode::new(I::Cmp, M::AbsoluteX, 3, 4),
+        0xDE => Opcode::new(I::Dec, M::AbsoluteX, 3, 7),
+        0xDF => Opcode::undocumented(I::Dcp, M::AbsoluteX, 3, 7),
+        0xE0 => Opcode::new(I::Cpx, M::Immediate, 2, 2),
+        0xE1 => Opcode::new(I::Sbc, M::IndexedIndirect, 2, 6),
+        0xE2 => Opcode::undocumented(I::Nop, M::Immediate, 2, 2),
+        0xE3 => Opcode::undocumented(I::Isc, M::IndexedIndirect, 2, 8),
+        0xE4 => Opcode::new(I::Cpx, M::ZeroPage, 2, 3),
+        0xE5 => Opcode::new(I::Sbc, M::ZeroPage, 2, 3),
+        0xE6 => Opcode::new(I::Inc, M::ZeroPage, 2, 5),
+        0xE7 => Opcode::undocumented(I::Isc, M::ZeroPage, 2, 5),
+        0xE8 => Opcode::new(I::Inx, M::Implicit, 1, 2),
+        0xE9 => Opcode::new(I::Sbc, M::Immediate, 2, 2),
+        0xEA => Opcode::new(I::Nop, M::Implicit, 1, 2),
+        0xEB => Opcode::undocumented(I::Sbc, M::Immediate, 2, 2),
+        0xEC => Opcode::new(I::Cpx, M::Absolute, 3, 4),
+        0xED => Opcode::new(I::Sbc, M::Absolute, 3, 4),
+        0xEE => Opcode::new(I::Inc, M::Absolute, 3, 6),
+        0xEF => Opcode::undocumented(I::Isc, M::Absolute, 3, 6),
+        0xF0 => Opcode::new(I::Beq, M::Relative, 2, 2),
+        0xF1 => Opcode::new(I::Sbc, M::IndirectIndexed, 2, 5),
+        0xF2 => Opcode::undocumented(I::Jam, M::Implicit, 1, 2),
+        0xF3 => Opcode::undocumented(I::Isc, M::IndirectIndexed, 2, 8),
+        0xF4 => Opcode::undocumented(I::Nop, M::ZeroPageX, 2, 4),
+        0xF5 => Opcode::new(I::Sbc, M::ZeroPageX, 2, 4),
+        0xF6 => Opcode::new(I::Inc, M::ZeroPageX, 2, 6),
+        0xF7 => Opcode::undocumented(I::Isc, M::ZeroPageX, 2, 6),
+        0xF8 => Opcode::new(I::Sed, M::Implicit, 1, 2),
+        0xF9 => Opcode::new(I::Sbc, M::AbsoluteY, 3, 4),
+        0xFA => Opcode::undocumented(I::Nop, M::Implicit, 1, 2),
+        0xFB => Opcode::undocumented(I::Isc, M::AbsoluteY, 3, 7),
+        0xFC => Opcode::undocumented(I::Nop, M::AbsoluteX, 3, 4),
+        0xFD => Opcode::new(I::Sbc, M::AbsoluteX, 3, 4),
+        0xFE => Opcode::new(I::Inc, M::AbsoluteX, 3, 7),
+        0xFF => Opcode::undocumented(I::Isc, M::AbsoluteX, 3, 7),
+    }
+}
+
+// The 65C02 opcodes that differ from the NMOS ones.
+fn decode_cmos(byte: u8) -> Option<Opcode> {
+    match byte {
+        0x02 => Some(Opcode::undocumented(I::Nop, M::Immediate, 2, 2)),
+        0x03 => Some(Opcode::undocumented(I::Nop, M::Implicit, 1, 1)),
+        0x04 => Some(Opcode::new(I::Tsb, M::ZeroPage, 2, 5)),
+        0x07 => Some(Opcode::new(I::Rmb(0), M::ZeroPage, 2, 5)),
+        0x0B => Some(Opcode::undocumented(I::Nop, M::Implicit, 1, 1)),
+        0x0C => Some(Opcode::new(I::Tsb, M::Absolute, 3, 6)),
+        0x0F => Some(Opcode::new(I::Bbr(0), M::ZeroPageRelative, 3, 5)),
+        0x12 => Some(Opcode::new(I::Ora, M::ZeroPageIndirect, 2, 5)),
+        0x13 => Some(Opcode::undocumented(I::Nop, M::Implicit, 1, 1)),
+        0x14 => Some(Opcode::new(I::Trb, M::ZeroPage, 2, 5)),
+        0x17 => Some(Opcode::new(I::Rmb(1), M::ZeroPage, 2, 5)),
+        0x1A => Some(Opcode::new(I::Inc, M::Accumulator, 1, 2)),
+        0x1B => Some(Opcode::undocumented(I::Nop, M::Implicit, 1, 1)),
+        0x1C => Some(Opcode::new(I::Trb, M::Absolute, 3, 6)),
+        0x1E => Some(Opcode::new(I::Asl, M::AbsoluteX, 3, 6)),
+        0x1F => Some(Opcode::new(I::Bbr(1), M::ZeroPageRelative, 3, 5)),
+        0x22 => Some(Opcode::undocumented(I::Nop, M::Immediate, 2, 2)),
+        0x23 => Some(Opcode::undocumented(I::Nop, M::Implicit, 1, 1)),
+        0x27 => Some(Opcode::new(I::Rmb(2), M::ZeroPage, 2, 5)),
+        0x2B => Some(Opcode::undocumented(I::Nop, M::Implicit, 1, 1)),
+        0x2F => Some(Opcode::new(I::Bbr(2), M::ZeroPageRelative, 3, 5)),
+        0x32 => Some(Opcode::new(I::And, M::ZeroPageIndirect, 2, 5)),
+        0x33 => Some(Opcode::undocumented(I::Nop, M::Implicit, 1, 1)),
+        0x34 => Some(Opcode::new(I::Bit, M::ZeroPageX, 2, 4)),
+        0x37 => Some(Opcode::new(I::Rmb(3), M::ZeroPage, 2, 5)),
+        0x3A => Some(Opcode::new(I::Dec, M::Accumulator, 1, 2)),
+        0x3B => Some(Opcode::undocumented(I::Nop, M::Implicit, 1, 1)),
+        0x3C => Some(Opcode::new(I::Bit, M::AbsoluteX, 3, 4)),
+        0x3E => Some(Opcode::new(I::Rol, M::AbsoluteX, 3, 6)),
+        0x3F => Some(Opcode::new(I::Bbr(3), M::ZeroPageRelative, 3, 5)),
+        0x42 => Some(Opcode::undocumented(I::Nop, M::Immediate, 2, 2)),
+        0x43 => Some(Opcode::undocumented(I::Nop, M::Implicit, 1, 1)),
+        0x47 => Some(Opcode::new(I::Rmb(4), M::ZeroPage, 2, 5)),
+        0x4B => Some(Opcode::undocumented(I::Nop, M::Implicit, 1, 1)),
+        0x4F => Some(Opcode::new(I::Bbr(4), M::ZeroPageRelative, 3, 5)),
+        0x52 => Some(Opcode::new(I::Eor, M::ZeroPageIndirect, 2, 5)),
+        0x53 => Some(Opcode::undocumented(I::Nop, M::Implicit, 1, 1)),
+        0x57 => Some(Opcode::new(I::Rmb(5), M::ZeroPage, 2, 5)),
+        0x5A => Some(Opcode::new(I::Phy, M::Implicit, 1, 3)),
+        0x5B => Some(Opcode::undocumented(I::Nop, M::Implicit, 1, 1)),
+        0x5C => Some(Opcode::undocumented(I::Nop, M::Absolute, 3, 8)),
+        0x5E => Some(Opcode::new(I::Lsr, M::AbsoluteX, 3, 6)),
+        0x5F => Some(Opcode::new(I::Bbr(5), M::ZeroPageRelative, 3, 5)),
+        0x62 => Some(Opcode::undocumented(I::Nop, M::Immediate, 2, 2)),
+        0x63 => Some(Opcode::undocumented(I::Nop, M::Implicit, 1, 1)),
+        0x64 => Some(Opcode::new(I::Stz, M::ZeroPage, 2, 3)),
+        0x67 => Some(Opcode::new(I::Rmb(6), M::ZeroPage, 2, 5)),
+        0x6B => Some(Opcode::undocumented(I::Nop, M::Implicit, 1, 1)),
+        0x6C => Some(Opcode::new(I::Jmp, M::Indirect, 3, 6)),
+        0x6F => Some(Opcode::new(I::Bbr(6), M::ZeroPageRelative, 3, 5)),
+        0x72 => Some(Opcode::new(I::Adc, M::ZeroPageIndirect, 2, 5)),
+        0x73 => Some(Opcode::undocumented(I::Nop, M::Implicit, 1, 1)),
+        0x74 => Some(Opcode::new(I::Stz, M::ZeroPageX, 2, 4)),
+        0x77 => Some(Opcode::new(I::Rmb(7), M::ZeroPage, 2, 5)),
+        0x7A => Some(Opcode::new(I::Ply, M::Implicit, 1, 4)),
+        0x7B => Some(Opcode::undocumented(I::Nop, M::Implicit, 1, 1)),
+        0x7C => Some(Opcode::new(I::Jmp, M::AbsoluteIndexedIndirect, 3, 6)),
+        0x7E => Some(Opcode::new(I::Ror, M::AbsoluteX, 3, 6)),
+        0x7F => Some(Opcode::new(I::Bbr(7), M::ZeroPageRelative, 3, 5)),
+        0x80 => Some(Opcode::new(I::Bra, M::Relative, 2, 2)),
+        0x83 => Some(Opcode::undocumented(I::Nop, M::Implicit, 1, 1)),
+        0x87 => Some(Opcode::new(I::Smb(0), M::ZeroPage, 2, 5)),
+        0x89 => Some(Opcode::new(I::Bit, M::Immediate, 2, 2)),
+        0x8B => Some(Opcode::undocumented(I::Nop, M::Implicit, 1, 1)),
+        0x8F => Some(Opcode::new(I::Bbs(0), M::ZeroPageRelative, 3, 5)),
+        0x92 => Some(Opcode::new(I::Sta, M::ZeroPageIndirect, 2, 5)),
+        0x93 => Some(Opcode::undocumented(I::Nop, M::Implicit, 1, 1)),
+        0x97 => Some(Opcode::new(I::Smb(1), M::ZeroPage, 2, 5)),
+        0x9B => Some(Opcode::undocumented(I::Nop, M::Implicit, 1, 1)),
+        0x9C => Some(Opcode::new(I::Stz, M::Absolute, 3, 4)),
+        0x9E => Some(Opcode::new(I::Stz, M::AbsoluteX, 3, 5)),
+        0x9F => Some(Opcode::new(I::Bbs(1), M::ZeroPageRelative, 3, 5)),
+        0xA3 => Some(Opcode::undocumented(I::Nop, M::Implicit, 1, 1)),
+        0xA7 => Some(Opcode::new(I::Smb(2), M::ZeroPage, 2, 5)),
+        0xAB => Some(Opcode::undocumented(I::Nop, M::Implicit, 1, 1)),
+        0xAF => Some(Opcode::new(I::Bbs(2), M::ZeroPageRelative, 3, 5)),
+        0xB2 => Some(Opcode::new(I::Lda, M::ZeroPageIndirect, 2, 5)),
+        0xB3 => Some(Opcode::undocumented(I::Nop, M::Implicit, 1, 1)),
+        0xB7 => Some(Opcode::new(I::Smb(3), M::ZeroPage, 2, 5)),
+        0xBB => Some(Opcode::undocumented(I::Nop, M::Implicit, 1, 1)),
+        0xBF => Some(Opcode::new(I::Bbs(3), M::ZeroPageRelative, 3, 5)),
+        0xC3 => Some(Opcode::undocumented(I::Nop, M::Implicit, 1, 1)),
+        0xC7 => Some(Opcode::new(I::Smb(4), M::ZeroPage, 2, 5)),
+        0xCB => Some(Opcode::new(I::Wai, M::Implicit, 1, 3)),
+        0xCF => Some(Opcode::new(I::Bbs(4), M::ZeroPageRelative, 3, 5)),
+        0xD2 => Some(Opcode::new(I::Cmp, M::ZeroPageIndirect, 2, 5)),
+        0xD3 => Some(Opcode::undocumented(I::Nop, M::Implicit, 1, 1)),
+        0xD7 => Some(Opcode::new(I::Smb(5), M::ZeroPage, 2, 5)),
+        0xDA => Some(Opcode::new(I::Phx, M::Implicit, 1, 3)),
+        0xDB => Some(Opcode::new(I::Stp, M::Implicit, 1, 3)),
+        0xDC => Some(Opcode::undocumented(I::Nop, M::Absolute, 3, 4)),
+        0xDF => Some(Opcode::new(I::Bbs(5), M::ZeroPageRelative, 3, 5)),
+        0xE3 => Some(Opcode::undocumented(I::Nop, M::Implicit, 1, 1)),
+        0xE7 => Some(Opcode::new(I::Smb(6), M::ZeroPage, 2, 5)),
+        0xEB => Some(Opcode::undocumented(I::Nop, M::Implicit, 1, 1)),
+        0xEF => Some(Opcode::new(I::Bbs(6), M::ZeroPageRelative, 3, 5)),
+        0xF2 => Some(Opcode::new(I::Sbc, M::ZeroPageIndirect, 2, 5)),
+        0xF3 => Some(Opcode::undocumented(I::Nop, M::Implicit, 1, 1)),
+        0xF7 => Some(Opcode::new(I::Smb(7), M::ZeroPage, 2, 5)),
+        0xFA => Some(Opcode::new(I::Plx, M::Implicit, 1, 4)),
+        0xFB => Some(Opcode::undocumented(I::Nop, M::Implicit, 1, 1)),
+        0xFC => Some(Opcode::undocumented(I::Nop, M::Absolute, 3, 4)),
+        0xFF => Some(Opcode::new(I::Bbs(7), M::ZeroPageRelative, 3, 5)),
+        _ => None,
+    }
+}
+
+fn decode_table(variant: CpuVariant, byte: u8) -> Opcode {
+    variant.opcodes()[byte as usize]
+}
+
+// A fixed pseudo-random byte stream, so that the branch predictor cannot
+// learn the sequence.
+fn stream() -> Vec<u8> {
+    let mut state = 0x2545_F491_u32;
+    (0..STREAM_LENGTH)
+        .map(|_| {
+            state ^= state << 13;
+            state ^= state >> 17;
+            state ^= state << 5;
+            state as u8
+        })
+        .collect()
+}
+
+fn time<F: Fn(u8) -> Opcode>(bytes: &[u8], decode: F) -> Duration {
+    let start = Instant::now();
+    for _ in 0..ROUNDS {
+        let mut total = 0u32;
+        for &byte in black_box(bytes) {
+            let opcode = decode(byte);
+            total += opcode.cycles as u32 + opcode.bytes as u32 + opcode.documented as u32;
+            total += opcode.mode as u32;
+        }
+        black_box(total);
+    }
+    start.elapsed()
+}
+
+#[test]
+fn match_agrees_with_the_tables() {
+    for byte in 0..=255 {
+        assert_eq!(
+            decode_match(false, byte),
+            decode_table(CpuVariant::Nmos6502, byte)
+        );
+        assert_eq!(
+            decode_match(true, byte),
+            decode_table(CpuVariant::Wdc65C02, byte)
+        );
+    }
+}
+
+#[test]
+#[ignore]
+fn table_dispatch_is_faster_than_match() {
+    let bytes = stream();
+    let decodes = (ROUNDS * STREAM_LENGTH) as f64;
+
+    for &variant in &[CpuVariant::Nmos6502, CpuVariant::Wdc65C02] {
+        let cmos = black_box(variant == CpuVariant::Wdc65C02);
+        let variant = black_box(variant);
+        let matched = time(&bytes, |byte| decode_match(cmos, byte));
+        let table = time(&bytes, |byte| decode_table(variant, byte));
+        println!(
+            "{:?}: match {:.2} ns, table {:.2} ns per decode",
+            variant,
+            matched.as_nanos() as f64 / decodes,
+            table.as_nanos() as f64 / decodes,
+        );
+        assert!(
+            table < matched,
+            "{:?}: table dispatch was not faster",
+            variant
+        );
+    }
+}
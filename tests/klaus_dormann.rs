@@ -0,0 +1,88 @@
+// Klaus Dormann's 6502 test suites (https://github.com/Klaus2m5/6502_65C02_functional_tests).
+// The assembled binaries are expected under tests/roms/.
+
+mod common;
+
+use goomba::bus::{Bus, Ram};
+use goomba::cpu::{Cpu, CpuVariant, StopConditions, StopReason};
+use std::fs;
+use std::path::Path;
+
+const FUNCTIONAL_TEST: &str = "tests/roms/6502_functional_test.bin";
+const DECIMAL_TEST: &str = "tests/roms/6502_decimal_test.bin";
+
+const LOAD_ADDRESS: u16 = 0x0000;
+const FUNCTIONAL_START: u16 = 0x0400;
+const DECIMAL_START: u16 = 0x0200;
+
+// Trap reached by the stock build of the functional test once every case passed.
+const FUNCTIONAL_SUCCESS: u16 = 0x3469;
+// The functional test records the case it is running here.
+const TEST_CASE: u16 = 0x0200;
+// The decimal test leaves a non-zero byte here when any result was wrong.
+const DECIMAL_ERROR: u16 = 0x000B;
+
+const INSTRUCTION_LIMIT: u64 = 100_000_000;
+
+fn load(path: &str, start: u16, on_break: bool) -> Option<Cpu<Ram>> {
+    if !common::available(&[Path::new(path)]) {
+        return None;
+    }
+
+    let image = fs::read(path).expect("failed to read test binary");
+    let mut ram = Ram::new();
+    ram.load(LOAD_ADDRESS, &image);
+
+    let mut cpu = Cpu::with_variant(CpuVariant::Nmos6502, ram);
+    cpu.set_undocumented_opcodes(false);
+    cpu.set_program_counter(start);
+    cpu.set_stop_conditions(StopConditions {
+        on_break,
+        on_self_jump: true,
+        instruction_limit: Some(INSTRUCTION_LIMIT),
+        ..StopConditions::default()
+    });
+    Some(cpu)
+}
+
+fn trap_address(reason: &StopReason) -> u16 {
+    match *reason {
+        StopReason::SelfJump { pc } | StopReason::Break { pc } => pc,
+        ref other => panic!("test did not reach a trap: {:?}", other),
+    }
+}
+
+#[test]
+fn functional_test() {
+    // The functional test executes BRK as one of its cases, so only its
+    // self-jump traps end the run.
+    let mut cpu = match load(FUNCTIONAL_TEST, FUNCTIONAL_START, false) {
+        Some(cpu) => cpu,
+        None => return,
+    };
+
+    let reason = cpu.run();
+    let trap = trap_address(&reason);
+    let test_case = cpu.bus_mut().read(TEST_CASE);
+
+    assert_eq!(
+        trap, FUNCTIONAL_SUCCESS,
+        "trapped at ${:04X} in test case ${:02X}",
+        trap, test_case
+    );
+}
+
+#[test]
+fn decimal_test() {
+    // The decimal test ends with a BRK.
+    let mut cpu = match load(DECIMAL_TEST, DECIMAL_START, true) {
+        Some(cpu) => cpu,
+        None => return,
+    };
+
+    let reason = cpu.run();
+    let trap = trap_address(&reason);
+    let error = cpu.bus_mut().read(DECIMAL_ERROR);
+
+    assert_eq!(error, 0, "decimal test failed, trapped at ${:04X}", trap);
+}
@@ -0,0 +1,71 @@
+// Kevin Horton's nestest.nes run in automation mode, compared line by line
+// against the Nintendulator golden log. Both files are expected under
+// tests/roms/.
+
+mod common;
+
+use goomba::bus::Bus;
+use goomba::cpu::{Cpu, CpuVariant};
+use goomba::trace;
+use std::fs;
+use std::path::Path;
+
+const ROM: &str = "tests/roms/nestest.nes";
+const LOG: &str = "tests/roms/nestest.log";
+
+const AUTOMATION_START: u16 = 0xC000;
+
+// Just enough of the NES memory map for nestest: mirrored internal RAM and
+// PRG ROM, with everything else reading as open bus.
+struct NesBus {
+    ram: [u8; 0x0800],
+    prg: Vec<u8>,
+}
+
+impl Bus for NesBus {
+    fn read(&mut self, address: u16) -> u8 {
+        match address {
+            0x0000..=0x1FFF => self.ram[address as usize & 0x07FF],
+            0x8000..=0xFFFF => self.prg[(address as usize - 0x8000) % self.prg.len()],
+            _ => 0xFF,
+        }
+    }
+
+    fn write(&mut self, address: u16, value: u8) {
+        if let 0x0000..=0x1FFF = address {
+            self.ram[address as usize & 0x07FF] = value;
+        }
+    }
+}
+
+#[test]
+fn nestest() {
+    if !common::available(&[Path::new(ROM), Path::new(LOG)]) {
+        return;
+    }
+
+    let prg = common::read_ines(Path::new(ROM)).prg;
+    let log = fs::read_to_string(LOG).expect("failed to read nestest.log");
+
+    let bus = NesBus {
+        ram: [0; 0x0800],
+        prg,
+    };
+    let mut cpu = Cpu::with_variant(CpuVariant::Ricoh2A03, bus);
+    cpu.power_on();
+    cpu.set_program_counter(AUTOMATION_START);
+
+    for (number, expected) in log.lines().enumerate() {
+        let actual = trace::nestest_line(&mut cpu);
+        assert_eq!(
+            actual,
+            expected.trim_end(),
+            "trace diverges from nestest.log at line {}",
+            number + 1
+        );
+
+        if let Err(error) = cpu.step() {
+            panic!("{} at line {}", error, number + 1);
+        }
+    }
+}
@@ -0,0 +1,242 @@
+// Runner for the community single-step tests
+// (https://github.com/SingleStepTests/65x02), one JSON file per opcode. Each
+// variant's files are expected in its own directory under
+// tests/roms/single_step/. Set SINGLE_STEP_BUS_LOG to also compare the cycle-by-cycle bus activity.
+
+mod common;
+
+use goomba::bus::Bus;
+use goomba::cpu::{Cpu, CpuState, CpuVariant};
+use goomba::error::CpuError;
+use goomba::status::StatusFlags;
+use serde_json::Value;
+use std::env;
+use std::ffi::OsStr;
+use std::fs;
+use std::mem;
+use std::path::{Path, PathBuf};
+
+const ROOT: &str = "tests/roms/single_step";
+const BUS_LOG: &str = "SINGLE_STEP_BUS_LOG";
+
+const REPORTED_FAILURES: usize = 20;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Access {
+    Read,
+    Write,
+}
+
+struct RecordingBus {
+    memory: Vec<u8>,
+    log: Vec<(u16, u8, Access)>,
+}
+
+impl Bus for RecordingBus {
+    fn read(&mut self, address: u16) -> u8 {
+        let value = self.memory[address as usize];
+        self.log.push((address, value, Access::Read));
+        value
+    }
+
+    fn write(&mut self, address: u16, value: u8) {
+        self.memory[address as usize] = value;
+        self.log.push((address, value, Access::Write));
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+struct State {
+    pc: u16,
+    s: u8,
+    a: u8,
+    x: u8,
+    y: u8,
+    p: u8,
+    ram: Vec<(u16, u8)>,
+}
+
+impl State {
+    fn parse(value: &Value) -> State {
+        let field = |name: &str| value[name].as_u64().expect(name);
+        let ram = value["ram"]
+            .as_array()
+            .expect("ram")
+            .iter()
+            .map(|entry| {
+                (
+                    entry[0].as_u64().expect("address") as u16,
+                    entry[1].as_u64().expect("value") as u8,
+                )
+            })
+            .collect();
+
+        State {
+            pc: field("pc") as u16,
+            s: field("s") as u8,
+            a: field("a") as u8,
+            x: field("x") as u8,
+            y: field("y") as u8,
+            p: field("p") as u8,
+            ram,
+        }
+    }
+
+    fn capture(cpu: &Cpu<RecordingBus>, addresses: &[(u16, u8)]) -> State {
+        let state = cpu.state();
+        State {
+            pc: state.program_counter,
+            s: state.stack_pointer,
+            a: state.accumulator,
+            x: state.index_x,
+            y: state.index_y,
+            p: state.status.bits(),
+            ram: addresses
+                .iter()
+                .map(|&(address, _)| (address, cpu.bus().memory[address as usize]))
+                .collect(),
+        }
+    }
+}
+
+fn parse_cycles(value: &Value) -> Vec<(u16, u8, Access)> {
+    value
+        .as_array()
+        .expect("cycles")
+        .iter()
+        .map(|cycle| {
+            let access = match cycle[2].as_str() {
+                Some("write") => Access::Write,
+                _ => Access::Read,
+            };
+            (
+                cycle[0].as_u64().expect("address") as u16,
+                cycle[1].as_u64().expect("value") as u8,
+                access,
+            )
+        })
+        .collect()
+}
+
+// Runs one test case on a fresh CPU, so that a JAM, WAI or STP in one case
+// cannot leak into the next, and describes the first mismatch, if any. The
+// memory is moved in and out of the bus to avoid reallocating it per case.
+fn run_case(
+    memory: &mut Vec<u8>,
+    variant: CpuVariant,
+    case: &Value,
+    compare_bus: bool,
+) -> Option<String> {
+    let initial = State::parse(&case["initial"]);
+    let expected = State::parse(&case["final"]);
+
+    for &(address, value) in &initial.ram {
+        memory[address as usize] = value;
+    }
+    let bus = RecordingBus {
+        memory: mem::take(memory),
+        log: Vec::new(),
+    };
+    let mut cpu = Cpu::with_variant(variant, bus);
+    cpu.set_undocumented_opcodes(true);
+    cpu.set_state(CpuState {
+        accumulator: initial.a,
+        index_x: initial.x,
+        index_y: initial.y,
+        status: StatusFlags::from_bits(initial.p),
+        stack_pointer: initial.s,
+        program_counter: initial.pc,
+    });
+
+    let result = cpu.step();
+    let actual = State::capture(&cpu, &expected.ram);
+
+    let failure = match result {
+        Err(error) if !cpu_halted(&error) => Some(format!("{}", error)),
+        _ if actual != expected => Some(format!("expected {:?}, got {:?}", expected, actual)),
+        _ if compare_bus && cpu.bus().log != parse_cycles(&case["cycles"]) => Some(format!(
+            "expected bus {:?}, got {:?}",
+            parse_cycles(&case["cycles"]),
+            cpu.bus().log
+        )),
+        _ => None,
+    };
+
+    *memory = mem::take(&mut cpu.bus_mut().memory);
+    for &(address, _) in initial.ram.iter().chain(&expected.ram) {
+        memory[address as usize] = 0;
+    }
+
+    failure
+}
+
+// Jammed opcodes stop the CPU, which is their expected final state.
+fn cpu_halted(error: &CpuError) -> bool {
+    matches!(error, CpuError::Halted { .. })
+}
+
+fn json_files(directory: &Path) -> Vec<PathBuf> {
+    let mut files: Vec<PathBuf> = fs::read_dir(directory)
+        .expect("failed to read test directory")
+        .map(|entry| entry.expect("failed to read directory entry").path())
+        .filter(|path| path.extension() == Some(OsStr::new("json")))
+        .collect();
+    files.sort();
+    files
+}
+
+fn run_variant(name: &str, variant: CpuVariant) {
+    let directory = Path::new(ROOT).join(name);
+    if !common::available(&[&directory]) {
+        return;
+    }
+
+    let compare_bus = env::var_os(BUS_LOG).is_some();
+    let mut memory = vec![0; 0x10000];
+
+    let mut failures = Vec::new();
+    let mut total = 0;
+
+    for path in json_files(&directory) {
+        let text = fs::read_to_string(&path).expect("failed to read test file");
+        let cases: Value = serde_json::from_str(&text).expect("failed to parse test file");
+        let opcode = path.file_stem().unwrap().to_string_lossy().into_owned();
+
+        for case in cases.as_array().expect("test file is not an array") {
+            total += 1;
+            if let Some(failure) = run_case(&mut memory, variant, case, compare_bus) {
+                let test = case["name"].as_str().unwrap_or("?");
+                failures.push(format!("{} [{}]: {}", opcode, test, failure));
+            }
+        }
+    }
+
+    assert!(
+        failures.is_empty(),
+        "{} of {} {} tests failed:\n{}",
+        failures.len(),
+        total,
+        name,
+        failures
+            .iter()
+            .take(REPORTED_FAILURES)
+            .cloned()
+            .collect::<Vec<_>>()
+            .join("\n")
+    );
+}
+
+#[test]
+fn nmos_6502() {
+    run_variant("6502", CpuVariant::Nmos6502);
+}
+
+#[test]
+fn ricoh_2a03() {
+    run_variant("nes6502", CpuVariant::Ricoh2A03);
+}
+
+#[test]
+fn wdc_65c02() {
+    run_variant("wdc65c02", CpuVariant::Wdc65C02);
+}